@@ -0,0 +1,114 @@
+//! Fast-forwarding of deterministic simulations, as they appear in many
+//! Advent of Code puzzles that ask for the state after a huge number of steps.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// The cycle a simulation ends up in: After `prefix_len` steps, the states
+/// repeat every `period` steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub prefix_len: usize,
+    pub period: usize,
+}
+
+/// The result of `fast_forward`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FastForwarded<S> {
+    /// The state after all steps.
+    pub state: S,
+    /// The cycle that was detected, or `None` if all steps were performed
+    /// before any state repeated.
+    pub cycle: Option<Cycle>,
+}
+
+/// Compute the state after applying `step` to `state` `n` times. Since `step`
+/// must be deterministic, the states repeat as soon as any state is seen a
+/// second time, so only the steps until then are actually performed.
+pub fn fast_forward<S, F>(state: S, mut step: F, n: usize) -> FastForwarded<S>
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&mut S),
+{
+    let mut first_seen_at: HashMap<S, usize> = HashMap::new();
+    let mut state = state;
+
+    for steps_done in 0..n {
+        if let Some(&prefix_len) = first_seen_at.get(&state) {
+            let cycle = Cycle {
+                prefix_len,
+                period: steps_done - prefix_len,
+            };
+
+            // The state after `n` steps is the one we have already seen after
+            // as many steps into the cycle as are left over after looping
+            // through the cycle as often as possible.
+            let final_steps = prefix_len + (n - prefix_len) % cycle.period;
+            let state = first_seen_at
+                .into_iter()
+                .find_map(|(state, seen_at)| (seen_at == final_steps).then_some(state))
+                .expect("all states before the repetition have been seen");
+
+            return FastForwarded {
+                state,
+                cycle: Some(cycle),
+            };
+        }
+
+        first_seen_at.insert(state.clone(), steps_done);
+        step(&mut state);
+    }
+
+    FastForwarded { state, cycle: None }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Step through `0, 1, .., 9, 3, 4, .., 9, 3, ..`.
+    fn step(n: &mut u32) {
+        *n = if *n == 9 { 3 } else { *n + 1 };
+    }
+
+    #[test]
+    fn test_fast_forward_detects_prefix_and_period() {
+        let cycle = Some(Cycle {
+            prefix_len: 3,
+            period: 7,
+        });
+
+        assert_eq!(
+            FastForwarded { state: 6, cycle },
+            fast_forward(0, step, 1_000_000_000)
+        );
+        assert_eq!(FastForwarded { state: 3, cycle }, fast_forward(0, step, 17));
+    }
+
+    #[test]
+    fn test_fast_forward_without_repetition() {
+        assert_eq!(
+            FastForwarded {
+                state: 6,
+                cycle: None
+            },
+            fast_forward(0, step, 6)
+        );
+        assert_eq!(
+            FastForwarded {
+                state: 0,
+                cycle: None
+            },
+            fast_forward(0, step, 0)
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_matches_simulation() {
+        for n in 0..50 {
+            let mut state = 0;
+            (0..n).for_each(|_| step(&mut state));
+
+            assert_eq!(state, fast_forward(0, step, n).state, "n = {}", n);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! Directions in which one can move through a `Grid`, either only along the
+//! rows and columns (`Direction`), or also diagonally (`CompassDirection`).
+
+use crate::{PositionInGrid, SignedPositionInGrid};
+use anyhow::bail;
+use parse_display::Display;
+use std::{iter, str};
+use strum::EnumIter;
+
+/// One of the four orthogonal directions in a grid. North points towards the
+/// first row, west points towards the first column.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum Direction {
+    #[display("^")]
+    North,
+
+    #[display(">")]
+    East,
+
+    #[display("v")]
+    South,
+
+    #[display("<")]
+    West,
+}
+
+impl Direction {
+    /// All directions, in clockwise order starting with `Direction::North`.
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    /// Invert the direction. Consider: if we were facing in this direction,
+    /// and turned around, in which direction would we be facing?
+    pub fn invert(&self) -> Self {
+        self.rotate(2)
+    }
+
+    /// Get the next direction, clockwise.
+    pub fn next_clockwise(&self) -> Self {
+        self.rotate(1)
+    }
+
+    /// Get the next direction, counter-clockwise.
+    pub fn next_counterclockwise(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    /// Rotate clockwise by `quarter_turns` quarter turns, where negative
+    /// values rotate counter-clockwise.
+    pub fn rotate(&self, quarter_turns: isize) -> Self {
+        let idx = (*self as isize + quarter_turns).rem_euclid(Self::ALL.len() as isize);
+        Self::ALL[idx as usize]
+    }
+
+    pub fn is_vertical(&self) -> bool {
+        matches!(self, Self::North | Self::South)
+    }
+
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Self::West | Self::East)
+    }
+
+    /// The change to the row and column indexes when moving one step into
+    /// this direction.
+    pub fn offset(&self) -> (isize, isize) {
+        CompassDirection::from(*self).offset()
+    }
+
+    /// Translate moving one step in this direction. This will never return
+    /// the same position back. If the step would lead to a negative row or
+    /// column index, return `None`.
+    pub fn translate(&self, pos: &PositionInGrid) -> Option<PositionInGrid> {
+        self.translate_n(pos, 1)
+    }
+
+    /// Translate moving `steps` steps in this direction. If any index would
+    /// become negative or overflow, return `None`.
+    pub fn translate_n(&self, pos: &PositionInGrid, steps: usize) -> Option<PositionInGrid> {
+        CompassDirection::from(*self).translate_n(pos, steps)
+    }
+
+    /// Translate moving `steps` steps in this direction in a grid without
+    /// fixed margins, where negative indexes are valid. Negative `steps` move
+    /// into the inverted direction. If any index would overflow, return
+    /// `None`.
+    pub fn translate_signed(
+        &self,
+        pos: &SignedPositionInGrid,
+        steps: isize,
+    ) -> Option<SignedPositionInGrid> {
+        CompassDirection::from(*self).translate_signed(pos, steps)
+    }
+
+    /// Move `steps` steps in this direction in a grid without fixed margins,
+    /// and return all newly visited positions, where the last element is the
+    /// furthest away from the initial `pos`.
+    pub fn translate_steps(
+        &self,
+        pos: &SignedPositionInGrid,
+        steps: usize,
+    ) -> impl Iterator<Item = SignedPositionInGrid> {
+        let direction = *self;
+        iter::successors(Some(*pos), move |pos| direction.translate_signed(pos, 1))
+            .skip(1)
+            .take(steps)
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = anyhow::Error;
+
+    /// Parse a direction from its relative (`U`, `R`, `D`, `L`), cardinal
+    /// (`N`, `E`, `S`, `W`) or arrow (`^`, `>`, `v`, `<`) representation.
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        let direction = match c {
+            'U' | 'N' | '^' => Self::North,
+            'R' | 'E' | '>' => Self::East,
+            'D' | 'S' | 'v' => Self::South,
+            'L' | 'W' | '<' => Self::West,
+            _ => bail!("invalid direction {:?}", c),
+        };
+        Ok(direction)
+    }
+}
+
+impl str::FromStr for Direction {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => c.try_into(),
+            _ => bail!("invalid direction {:?}", s),
+        }
+    }
+}
+
+/// One of the eight directions of a compass rose in a grid, which includes
+/// the four orthogonal and the four diagonal directions.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum CompassDirection {
+    #[display("N")]
+    North,
+
+    #[display("NE")]
+    NorthEast,
+
+    #[display("E")]
+    East,
+
+    #[display("SE")]
+    SouthEast,
+
+    #[display("S")]
+    South,
+
+    #[display("SW")]
+    SouthWest,
+
+    #[display("W")]
+    West,
+
+    #[display("NW")]
+    NorthWest,
+}
+
+impl CompassDirection {
+    /// All directions, in clockwise order starting with
+    /// `CompassDirection::North`.
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+
+    /// Invert the direction. Consider: if we were facing in this direction,
+    /// and turned around, in which direction would we be facing?
+    pub fn invert(&self) -> Self {
+        self.rotate(4)
+    }
+
+    /// Get the next direction, clockwise, which is an eighth turn away.
+    pub fn next_clockwise(&self) -> Self {
+        self.rotate(1)
+    }
+
+    /// Get the next direction, counter-clockwise, which is an eighth turn
+    /// away.
+    pub fn next_counterclockwise(&self) -> Self {
+        self.rotate(-1)
+    }
+
+    /// Rotate clockwise by `eighth_turns` eighth turns, where negative values
+    /// rotate counter-clockwise.
+    pub fn rotate(&self, eighth_turns: isize) -> Self {
+        let idx = (*self as isize + eighth_turns).rem_euclid(Self::ALL.len() as isize);
+        Self::ALL[idx as usize]
+    }
+
+    pub fn is_diagonal(&self) -> bool {
+        let (row_delta, col_delta) = self.offset();
+        row_delta != 0 && col_delta != 0
+    }
+
+    /// The change to the row and column indexes when moving one step into
+    /// this direction.
+    pub fn offset(&self) -> (isize, isize) {
+        match self {
+            Self::North => (-1, 0),
+            Self::NorthEast => (-1, 1),
+            Self::East => (0, 1),
+            Self::SouthEast => (1, 1),
+            Self::South => (1, 0),
+            Self::SouthWest => (1, -1),
+            Self::West => (0, -1),
+            Self::NorthWest => (-1, -1),
+        }
+    }
+
+    /// Translate moving one step in this direction. This will never return
+    /// the same position back. If the step would lead to a negative row or
+    /// column index, return `None`.
+    pub fn translate(&self, pos: &PositionInGrid) -> Option<PositionInGrid> {
+        self.translate_n(pos, 1)
+    }
+
+    /// Translate moving `steps` steps in this direction. If any index would
+    /// become negative or overflow, return `None`.
+    pub fn translate_n(&self, pos: &PositionInGrid, steps: usize) -> Option<PositionInGrid> {
+        let steps = isize::try_from(steps).ok()?;
+        let (row_delta, col_delta) = self.offset();
+        let row = pos.row.checked_add_signed(row_delta.checked_mul(steps)?)?;
+        let col = pos.col.checked_add_signed(col_delta.checked_mul(steps)?)?;
+        Some(PositionInGrid { row, col })
+    }
+
+    /// Translate moving `steps` steps in this direction in a grid without
+    /// fixed margins, where negative indexes are valid. Negative `steps` move
+    /// into the inverted direction. If any index would overflow, return
+    /// `None`.
+    pub fn translate_signed(
+        &self,
+        pos: &SignedPositionInGrid,
+        steps: isize,
+    ) -> Option<SignedPositionInGrid> {
+        let (row_delta, col_delta) = self.offset();
+        let row = pos.row.checked_add(row_delta.checked_mul(steps)?)?;
+        let col = pos.col.checked_add(col_delta.checked_mul(steps)?)?;
+        Some(SignedPositionInGrid { row, col })
+    }
+}
+
+impl From<Direction> for CompassDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::North => Self::North,
+            Direction::East => Self::East,
+            Direction::South => Self::South,
+            Direction::West => Self::West,
+        }
+    }
+}
+
+impl str::FromStr for CompassDirection {
+    type Err = anyhow::Error;
+
+    /// Parse a direction from its cardinal representation (e.g. `N` or `SW`),
+    /// or from any representation accepted by `Direction`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let direction = match s {
+            "NE" => Self::NorthEast,
+            "SE" => Self::SouthEast,
+            "SW" => Self::SouthWest,
+            "NW" => Self::NorthWest,
+            _ => s.parse::<Direction>()?.into(),
+        };
+        Ok(direction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(Direction::East, Direction::North.next_clockwise());
+        assert_eq!(Direction::West, Direction::North.next_counterclockwise());
+        assert_eq!(Direction::North, Direction::West.next_clockwise());
+        assert_eq!(Direction::South, Direction::East.rotate(-3));
+        assert_eq!(
+            CompassDirection::NorthWest,
+            CompassDirection::North.next_counterclockwise()
+        );
+
+        for direction in Direction::iter() {
+            assert_eq!(direction, direction.invert().invert());
+            assert_ne!(direction, direction.invert());
+        }
+        for direction in CompassDirection::iter() {
+            let (row_delta, col_delta) = direction.offset();
+            assert_eq!((-row_delta, -col_delta), direction.invert().offset());
+        }
+    }
+
+    #[test]
+    fn test_translate() {
+        let pos = PositionInGrid::new(1, 1);
+        assert_eq!(
+            Some(PositionInGrid::new(0, 1)),
+            Direction::North.translate(&pos)
+        );
+        assert_eq!(
+            Some(PositionInGrid::new(2, 1)),
+            Direction::South.translate(&pos)
+        );
+        assert_eq!(
+            Some(PositionInGrid::new(1, 2)),
+            Direction::East.translate(&pos)
+        );
+        assert_eq!(
+            Some(PositionInGrid::new(1, 0)),
+            Direction::West.translate(&pos)
+        );
+        assert_eq!(None, Direction::West.translate_n(&pos, 2));
+        assert_eq!(
+            Some(PositionInGrid::new(4, 4)),
+            CompassDirection::SouthEast.translate_n(&pos, 3)
+        );
+        assert_eq!(
+            None,
+            Direction::South.translate(&PositionInGrid::new(usize::MAX, 0))
+        );
+    }
+
+    #[test]
+    fn test_translate_signed() {
+        let pos = SignedPositionInGrid::new(0, 0);
+        assert_eq!(
+            Some(SignedPositionInGrid::new(-3, 0)),
+            Direction::North.translate_signed(&pos, 3)
+        );
+        assert_eq!(
+            Some(SignedPositionInGrid::new(0, 2)),
+            Direction::West.translate_signed(&pos, -2)
+        );
+        assert_eq!(
+            vec![
+                SignedPositionInGrid::new(0, -1),
+                SignedPositionInGrid::new(0, -2)
+            ],
+            Direction::West.translate_steps(&pos, 2).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        for (s, expected) in [
+            ("U", Direction::North),
+            ("E", Direction::East),
+            ("v", Direction::South),
+            ("L", Direction::West),
+        ] {
+            assert_eq!(expected, s.parse::<Direction>().unwrap());
+        }
+        assert!("X".parse::<Direction>().is_err());
+        assert!("UU".parse::<Direction>().is_err());
+
+        assert_eq!(
+            CompassDirection::SouthWest,
+            "SW".parse::<CompassDirection>().unwrap()
+        );
+        assert_eq!(
+            CompassDirection::East,
+            ">".parse::<CompassDirection>().unwrap()
+        );
+    }
+}
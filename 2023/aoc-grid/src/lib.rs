@@ -0,0 +1,643 @@
+//! A generic two-dimensional grid, as it appears in many Advent of Code
+//! puzzles, where the puzzle input contains one character per grid cell.
+
+mod direction;
+
+pub use direction::{CompassDirection, Direction};
+
+use aoc_parse::ParseError;
+use derive_new::new;
+use itertools::{Itertools, Position};
+use std::{
+    fmt,
+    iter::StepBy,
+    ops::{Index, IndexMut},
+    slice, str,
+};
+
+/// An index into a row or column of a `Grid`.
+pub type GridIndex = usize;
+
+/// The position of a cell inside a `Grid`.
+#[derive(Debug, new, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PositionInGrid {
+    pub row: GridIndex,
+    pub col: GridIndex,
+}
+
+impl PositionInGrid {
+    /// The number of orthogonal steps between this position and `other`.
+    pub fn manhattan_distance(&self, other: &PositionInGrid) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+/// An index into a row or column of a grid without fixed margins, which may
+/// be negative.
+pub type SignedGridIndex = isize;
+
+/// The position of a cell inside a grid without fixed margins, e.g. one that
+/// is only built up while moving through it.
+#[derive(Debug, new, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SignedPositionInGrid {
+    pub row: SignedGridIndex,
+    pub col: SignedGridIndex,
+}
+
+impl SignedPositionInGrid {
+    /// Convert this position to a `PositionInGrid` in a grid whose first row
+    /// and column are at `origin`. If this position lies above or left of
+    /// `origin`, return `None`.
+    pub fn relative_to(&self, origin: &SignedPositionInGrid) -> Option<PositionInGrid> {
+        let row = usize::try_from(self.row.checked_sub(origin.row)?).ok()?;
+        let col = usize::try_from(self.col.checked_sub(origin.col)?).ok()?;
+        Some(PositionInGrid { row, col })
+    }
+}
+
+/// A two-dimensional grid of cells of type `T`, stored in row-major order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl<T> Grid<T> {
+    /// Create a grid from cells in row-major order, where each row has `cols`
+    /// cells. A grid without columns has no rows either.
+    ///
+    /// # Panics
+    ///
+    /// If the number of cells is not a multiple of `cols`, i.e. also if there
+    /// are cells but no columns.
+    pub fn from_vec(cells: Vec<T>, cols: usize) -> Self {
+        let rows = match cols {
+            0 => {
+                assert!(cells.is_empty(), "cells without columns cannot form a grid");
+                0
+            }
+            cols => {
+                assert_eq!(
+                    0,
+                    cells.len() % cols,
+                    "the number of cells must be a multiple of the number of columns"
+                );
+                cells.len() / cols
+            }
+        };
+
+        Self { cells, rows, cols }
+    }
+
+    /// Parse a grid from text containing one line per row and one character
+    /// per cell. Each cell is created from its position and character by
+    /// `parse_cell`. All lines must have the same number of characters.
+    /// Errors point to the offending row or cell.
+    pub fn parse_with<F>(s: &str, mut parse_cell: F) -> anyhow::Result<Self>
+    where
+        F: FnMut(PositionInGrid, char) -> anyhow::Result<T>,
+    {
+        let cols = s
+            .lines()
+            .next()
+            .map_or(0, |first_row| first_row.chars().count());
+
+        let mut cells = Vec::with_capacity(s.len());
+        for (row, line) in s.lines().enumerate() {
+            let line_cols = line.chars().count();
+            if line_cols != cols {
+                let message = format!("expected {} columns, but found {}", cols, line_cols);
+                return Err(ParseError::at(s, line, message).into());
+            }
+
+            for (col, (offset, c)) in line.char_indices().enumerate() {
+                let cell = parse_cell(PositionInGrid { row, col }, c).map_err(|err| {
+                    ParseError::at(s, &line[offset..], format!("invalid cell: {:#}", err))
+                })?;
+                cells.push(cell);
+            }
+        }
+
+        Ok(Self::from_vec(cells, cols))
+    }
+
+    /// The number of rows.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// The number of columns.
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// The number of rows and columns.
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    /// Check whether a position lies inside the grid bounds.
+    pub fn contains(&self, pos: &PositionInGrid) -> bool {
+        pos.row < self.rows && pos.col < self.cols
+    }
+
+    /// Get the cell at a position, if it lies inside the grid bounds.
+    pub fn get(&self, pos: &PositionInGrid) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.cells[pos.row * self.cols + pos.col])
+    }
+
+    /// Get the cell at a position mutably, if it lies inside the grid bounds.
+    pub fn get_mut(&mut self, pos: &PositionInGrid) -> Option<&mut T> {
+        self.contains(pos)
+            .then(|| &mut self.cells[pos.row * self.cols + pos.col])
+    }
+
+    /// Swap the cells at two positions.
+    ///
+    /// # Panics
+    ///
+    /// If either position lies outside the grid bounds.
+    pub fn swap(&mut self, pos_a: &PositionInGrid, pos_b: &PositionInGrid) {
+        assert!(self.contains(pos_a) && self.contains(pos_b));
+        self.cells.swap(
+            pos_a.row * self.cols + pos_a.col,
+            pos_b.row * self.cols + pos_b.col,
+        );
+    }
+
+    /// Get the position that is `row_delta` rows and `col_delta` columns away
+    /// from `pos`, if it lies inside the grid bounds.
+    pub fn neighbour(
+        &self,
+        pos: &PositionInGrid,
+        row_delta: isize,
+        col_delta: isize,
+    ) -> Option<PositionInGrid> {
+        let row = pos.row.checked_add_signed(row_delta)?;
+        let col = pos.col.checked_add_signed(col_delta)?;
+        let neighbour = PositionInGrid { row, col };
+        self.contains(&neighbour).then_some(neighbour)
+    }
+
+    /// Get the positions of the up to four neighbours that share a side with
+    /// `pos`.
+    pub fn orthogonal_neighbours(
+        &self,
+        pos: &PositionInGrid,
+    ) -> impl Iterator<Item = PositionInGrid> + '_ {
+        let pos = *pos;
+        Direction::ALL.into_iter().filter_map(move |direction| {
+            let (row_delta, col_delta) = direction.offset();
+            self.neighbour(&pos, row_delta, col_delta)
+        })
+    }
+
+    /// Get the positions of the up to eight neighbours that share a side or a
+    /// corner with `pos`.
+    pub fn all_neighbours(
+        &self,
+        pos: &PositionInGrid,
+    ) -> impl Iterator<Item = PositionInGrid> + '_ {
+        let pos = *pos;
+        CompassDirection::ALL
+            .into_iter()
+            .filter_map(move |direction| {
+                let (row_delta, col_delta) = direction.offset();
+                self.neighbour(&pos, row_delta, col_delta)
+            })
+    }
+
+    /// Iterate over all cells in row-major order.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    /// Iterate mutably over all cells in row-major order.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Iterate over all positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = PositionInGrid> {
+        let cols = self.cols;
+        (0..self.rows).flat_map(move |row| (0..cols).map(move |col| PositionInGrid { row, col }))
+    }
+
+    /// Iterate over all cells together with their positions in row-major
+    /// order.
+    pub fn indexed_iter(&self) -> impl Iterator<Item = (PositionInGrid, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// Iterate over the cells of a single row.
+    ///
+    /// # Panics
+    ///
+    /// If the row lies outside the grid bounds.
+    pub fn iter_row(&self, row: GridIndex) -> slice::Iter<'_, T> {
+        assert!(row < self.rows, "row {} is out of bounds", row);
+        self.cells[row * self.cols..(row + 1) * self.cols].iter()
+    }
+
+    /// Iterate over the cells of a single column.
+    ///
+    /// # Panics
+    ///
+    /// If the column lies outside the grid bounds.
+    pub fn iter_col(&self, col: GridIndex) -> StepBy<slice::Iter<'_, T>> {
+        assert!(col < self.cols, "column {} is out of bounds", col);
+        // Without rows, there is no cell the column could start at.
+        let cells = self.cells.get(col..).unwrap_or_default();
+        cells.iter().step_by(self.cols)
+    }
+
+    /// Iterate over all rows, from top to bottom.
+    pub fn iter_rows(&self) -> impl Iterator<Item = slice::Iter<'_, T>> {
+        (0..self.rows).map(|row| self.iter_row(row))
+    }
+
+    /// Iterate over all columns, from left to right.
+    pub fn iter_cols(&self) -> impl Iterator<Item = StepBy<slice::Iter<'_, T>>> {
+        (0..self.cols).map(|col| self.iter_col(col))
+    }
+
+    /// Iterate over all diagonals that go from the top-left to the
+    /// bottom-right. The diagonals start at the bottom-left corner and end at
+    /// the top-right corner.
+    pub fn iter_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let starts = (0..self.rows)
+            .rev()
+            .map(|row| PositionInGrid { row, col: 0 })
+            .chain((1..self.cols).map(|col| PositionInGrid { row: 0, col }));
+
+        starts.map(|start| {
+            (0..)
+                .map(move |step| PositionInGrid::new(start.row + step, start.col + step))
+                .map_while(|pos| self.get(&pos))
+        })
+    }
+
+    /// Iterate over all anti-diagonals that go from the top-right to the
+    /// bottom-left. The anti-diagonals start at the top-left corner and end at
+    /// the bottom-right corner.
+    pub fn iter_anti_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        let last_col = self.cols.checked_sub(1);
+        let starts = (0..self.cols)
+            .map(|col| PositionInGrid { row: 0, col })
+            .chain(
+                last_col
+                    .into_iter()
+                    .flat_map(|col| (1..self.rows).map(move |row| PositionInGrid { row, col })),
+            );
+
+        starts.map(|start| {
+            (0..=start.col)
+                .map(move |step| PositionInGrid::new(start.row + step, start.col - step))
+                .map_while(|pos| self.get(&pos))
+        })
+    }
+
+    /// Insert a row before the row at index `row`.
+    ///
+    /// # Panics
+    ///
+    /// If the row has the wrong length, or `row` lies outside the grid bounds
+    /// (inserting after the last row is allowed).
+    pub fn insert_row(&mut self, row: GridIndex, cells: Vec<T>) {
+        assert_eq!(self.cols, cells.len(), "inserted row has the wrong length");
+        assert!(row <= self.rows, "row {} is out of bounds", row);
+
+        let idx = row * self.cols;
+        self.cells.splice(idx..idx, cells);
+        // Just like for `from_vec`, a grid without columns has no rows.
+        if self.cols > 0 {
+            self.rows += 1;
+        }
+    }
+
+    /// Insert a column before the column at index `col`.
+    ///
+    /// # Panics
+    ///
+    /// If the column has the wrong length, or `col` lies outside the grid
+    /// bounds (inserting after the last column is allowed).
+    pub fn insert_col(&mut self, col: GridIndex, cells: Vec<T>) {
+        assert_eq!(
+            self.rows,
+            cells.len(),
+            "inserted column has the wrong length"
+        );
+        assert!(col <= self.cols, "column {} is out of bounds", col);
+
+        // Insert from the back to not interfere with indexes.
+        for (row, cell) in cells.into_iter().enumerate().rev() {
+            self.cells.insert(row * self.cols + col, cell);
+        }
+        self.cols += 1;
+    }
+
+    /// Apply `f` to every cell, creating a new grid of the same size.
+    pub fn map<U, F>(self, f: F) -> Grid<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        Grid {
+            cells: self.cells.into_iter().map(f).collect(),
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    /// Apply `f` to every cell together with its position, creating a new
+    /// grid of the same size.
+    pub fn map_with_position<U, F>(self, mut f: F) -> Grid<U>
+    where
+        F: FnMut(PositionInGrid, T) -> U,
+    {
+        let cols = self.cols;
+        let cells = self
+            .cells
+            .into_iter()
+            .enumerate()
+            .map(|(idx, cell)| {
+                let pos = PositionInGrid {
+                    row: idx / cols,
+                    col: idx % cols,
+                };
+                f(pos, cell)
+            })
+            .collect();
+
+        Grid {
+            cells,
+            rows: self.rows,
+            cols: self.cols,
+        }
+    }
+
+    /// Consume the grid and return its cells in row-major order.
+    pub fn into_vec(self) -> Vec<T> {
+        self.cells
+    }
+}
+
+impl<T: Clone> Grid<T> {
+    /// Create a grid where every cell is a clone of `cell`.
+    /// Just like for `from_vec`, there are no rows if there are no `cols`.
+    pub fn filled(rows: usize, cols: usize, cell: T) -> Self {
+        Self::from_vec(vec![cell; rows * cols], cols)
+    }
+
+    /// Create the transposed grid, where rows become columns and columns
+    /// become rows.
+    pub fn transpose(&self) -> Self {
+        let cells = self.iter_cols().flatten().cloned().collect();
+        Self::from_vec(cells, self.rows)
+    }
+}
+
+impl<T> Index<PositionInGrid> for Grid<T> {
+    type Output = T;
+    fn index(&self, pos: PositionInGrid) -> &Self::Output {
+        self.get(&pos)
+            .unwrap_or_else(|| panic!("position {:?} is out of bounds", pos))
+    }
+}
+
+impl<T> IndexMut<PositionInGrid> for Grid<T> {
+    fn index_mut(&mut self, pos: PositionInGrid) -> &mut Self::Output {
+        self.get_mut(&pos)
+            .unwrap_or_else(|| panic!("position {:?} is out of bounds", pos))
+    }
+}
+
+impl<T> From<Vec<Vec<T>>> for Grid<T> {
+    /// Create a grid from its rows.
+    ///
+    /// # Panics
+    ///
+    /// If the rows don't all have the same length.
+    fn from(rows: Vec<Vec<T>>) -> Self {
+        let cols = rows.first().map_or(0, |first| first.len());
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "all rows must have the same length"
+        );
+        Self::from_vec(rows.into_iter().flatten().collect(), cols)
+    }
+}
+
+impl<T> str::FromStr for Grid<T>
+where
+    T: str::FromStr,
+    anyhow::Error: From<T::Err>,
+{
+    type Err = anyhow::Error;
+
+    /// Parse from one line per row and one character per cell, where each
+    /// cell is parsed from its character with `T::from_str`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, |_pos, c| Ok(c.to_string().parse::<T>()?))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    /// Display one line per row, without a trailing newline.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, row) in self.iter_rows().with_position() {
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+            if let Position::First | Position::Middle = position {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    /// A 3x4 grid of digits:
+    /// ```text
+    /// 0123
+    /// 4567
+    /// 89ab
+    /// ```
+    fn example_grid() -> Grid<char> {
+        Grid::parse_with("0123\n4567\n89ab", |_pos, c| Ok(c)).unwrap()
+    }
+
+    fn collect<'a>(iter: impl Iterator<Item = &'a char>) -> String {
+        iter.collect()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let input = indoc! {"
+            #.#
+            ..#
+        "}
+        .trim_end();
+        let grid: Grid<char> = input.parse().unwrap();
+
+        assert_eq!((2, 3), grid.size());
+        assert_eq!(Some(&'#'), grid.get(&PositionInGrid::new(1, 2)));
+        assert_eq!(input, grid.to_string());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("..\n.".parse::<Grid<char>>().is_err());
+        assert!("12\n3x".parse::<Grid<u8>>().is_err());
+
+        let err = "12\n3x".parse::<Grid<u8>>().unwrap_err();
+        let err = err.downcast_ref::<ParseError>().unwrap();
+        assert_eq!((2, 2, "x"), (err.line, err.column, err.snippet.as_str()));
+        assert_eq!((0, 0), "".parse::<Grid<char>>().unwrap().size());
+    }
+
+    #[test]
+    fn test_parse_with_position() {
+        let grid = Grid::parse_with("ab\ncd", |pos, c| Ok((pos, c))).unwrap();
+        let pos = PositionInGrid::new(1, 0);
+        assert_eq!((pos, 'c'), grid[pos]);
+    }
+
+    #[test]
+    fn test_manhattan_distance() {
+        let a = PositionInGrid::new(1, 4);
+        let b = PositionInGrid::new(3, 0);
+        assert_eq!(6, a.manhattan_distance(&b));
+        assert_eq!(6, b.manhattan_distance(&a));
+        assert_eq!(0, a.manhattan_distance(&a));
+    }
+
+    #[test]
+    fn test_get_out_of_bounds() {
+        let grid = example_grid();
+        assert_eq!(None, grid.get(&PositionInGrid::new(3, 0)));
+        assert_eq!(None, grid.get(&PositionInGrid::new(0, 4)));
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = example_grid();
+        let corner = PositionInGrid::new(0, 0);
+        let center = PositionInGrid::new(1, 1);
+
+        assert_eq!(None, grid.neighbour(&corner, -1, 0));
+        assert_eq!(
+            Some(PositionInGrid::new(1, 1)),
+            grid.neighbour(&corner, 1, 1)
+        );
+
+        assert_eq!(2, grid.orthogonal_neighbours(&corner).count());
+        assert_eq!(3, grid.all_neighbours(&corner).count());
+        assert_eq!(4, grid.orthogonal_neighbours(&center).count());
+        assert_eq!(8, grid.all_neighbours(&center).count());
+    }
+
+    #[test]
+    fn test_rows_and_cols() {
+        let grid = example_grid();
+        assert_eq!("4567", collect(grid.iter_row(1)));
+        assert_eq!("159", collect(grid.iter_col(1)));
+        assert_eq!("951", collect(grid.iter_col(1).rev()));
+        assert_eq!(
+            vec!["0123", "4567", "89ab"],
+            grid.iter_rows().map(collect).collect_vec()
+        );
+        assert_eq!(
+            vec!["048", "159", "26a", "37b"],
+            grid.iter_cols().map(collect).collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let grid = example_grid();
+        assert_eq!(
+            vec!["8", "49", "05a", "16b", "27", "3"],
+            grid.iter_diagonals().map(collect).collect_vec()
+        );
+        assert_eq!(
+            vec!["0", "14", "258", "369", "7a", "b"],
+            grid.iter_anti_diagonals().map(collect).collect_vec()
+        );
+    }
+
+    #[test]
+    fn test_transpose() {
+        let grid = example_grid();
+        let transposed = grid.transpose();
+        assert_eq!("048\n159\n26a\n37b", transposed.to_string());
+        assert_eq!(grid, transposed.transpose());
+    }
+
+    #[test]
+    fn test_empty_grids() {
+        let grid: Grid<char> = Grid::from_vec(vec![], 3);
+        assert_eq!((0, 3), (grid.rows(), grid.cols()));
+        assert_eq!(0, grid.iter_rows().count());
+        assert_eq!(vec![""; 3], grid.iter_cols().map(collect).collect_vec());
+        assert_eq!(
+            vec!["", "", ""],
+            grid.iter_anti_diagonals().map(collect).collect_vec()
+        );
+
+        // Without columns, there are no rows either.
+        let transposed = grid.transpose();
+        assert_eq!((0, 0), (transposed.rows(), transposed.cols()));
+        assert_eq!(Grid::from_vec(vec![], 0), transposed);
+
+        for grid in [Grid::filled(3, 0, '0'), Grid::filled(0, 0, '0')] {
+            assert_eq!((0, 0), (grid.rows(), grid.cols()));
+            assert_eq!(0, grid.iter_rows().count());
+            assert_eq!(0, grid.iter_cols().count());
+            assert_eq!(0, grid.iter_diagonals().count());
+            assert_eq!(0, grid.iter_anti_diagonals().count());
+            assert_eq!(grid, grid.transpose());
+        }
+
+        let mut grid: Grid<char> = Grid::filled(0, 0, '0');
+        grid.insert_row(0, vec![]);
+        assert_eq!((0, 0), (grid.rows(), grid.cols()));
+        grid.insert_col(0, vec![]);
+        assert_eq!((0, 1), (grid.rows(), grid.cols()));
+        grid.insert_row(0, vec!['x']);
+        assert_eq!("x", grid.to_string());
+    }
+
+    #[test]
+    #[should_panic(expected = "cells without columns")]
+    fn test_cells_without_cols() {
+        Grid::from_vec(vec!['0', '1'], 0);
+    }
+
+    #[test]
+    fn test_insert_row_and_col() {
+        let mut grid = example_grid();
+        grid.insert_row(1, vec!['x'; 4]);
+        grid.insert_col(4, vec!['y'; 4]);
+        assert_eq!("0123y\nxxxxy\n4567y\n89aby", grid.to_string());
+    }
+
+    #[test]
+    fn test_map_with_position() {
+        let grid = example_grid().map_with_position(|pos, _c| pos.row * 10 + pos.col);
+        assert_eq!("0123\n10111213\n20212223", grid.to_string());
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut grid = example_grid();
+        grid.swap(&PositionInGrid::new(0, 0), &PositionInGrid::new(2, 3));
+        assert_eq!("b123\n4567\n89a0", grid.to_string());
+    }
+}
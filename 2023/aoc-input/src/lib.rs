@@ -0,0 +1,161 @@
+//! Loading of puzzle inputs at runtime, either from a file, from stdin, or
+//! from the puzzle input bundled with a day.
+
+use anyhow::Context;
+use clap::{Args, Parser};
+use std::{
+    convert::Infallible,
+    env, fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Where a puzzle input is read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Stdin,
+    File(PathBuf),
+}
+
+impl FromStr for InputSource {
+    type Err = Infallible;
+
+    /// `-` stands for stdin, anything else is a path to a file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "-" => InputSource::Stdin,
+            path => InputSource::File(PathBuf::from(path)),
+        })
+    }
+}
+
+impl fmt::Display for InputSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputSource::Stdin => write!(f, "stdin"),
+            InputSource::File(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl InputSource {
+    /// Read the whole puzzle input from this source.
+    pub fn read(&self) -> anyhow::Result<String> {
+        let read = match self {
+            InputSource::Stdin => {
+                let mut puzzle_input = String::new();
+                io::stdin()
+                    .read_to_string(&mut puzzle_input)
+                    .map(|_| puzzle_input)
+            }
+            InputSource::File(path) => fs::read_to_string(path),
+        };
+
+        read.with_context(|| format!("failed to read puzzle input from {}", self))
+    }
+}
+
+/// The environment variable that points to the workspace to read the bundled
+/// puzzle inputs from, see `read_bundled`.
+pub const WORKSPACE_VAR: &str = "AOC_WORKSPACE";
+
+/// Read the puzzle input bundled with a day from the file at `path`.
+///
+/// The path is baked in at build time as an absolute path into the workspace
+/// the day was built in, so a binary that was copied or installed elsewhere
+/// only finds it as long as that workspace stays in place. If `AOC_WORKSPACE`
+/// is set, the path is resolved relative to that workspace instead.
+pub fn read_bundled(path: impl AsRef<Path>) -> anyhow::Result<String> {
+    let workspace = env::var_os(WORKSPACE_VAR).map(PathBuf::from);
+    let path = resolve_bundled(path.as_ref(), workspace.as_deref());
+    fs::read_to_string(&path).with_context(|| {
+        format!(
+            "failed to read the bundled puzzle input from {}; \
+            save your puzzle input there, point {} to the workspace or select another one",
+            path.display(),
+            WORKSPACE_VAR
+        )
+    })
+}
+
+/// Move the bundled puzzle input at `path`, i.e. `<day crate>/<file>`, into
+/// the `workspace`, if any.
+fn resolve_bundled(path: &Path, workspace: Option<&Path>) -> PathBuf {
+    let in_day_crate = path
+        .parent()
+        .and_then(Path::file_name)
+        .zip(path.file_name());
+
+    match (workspace, in_day_crate) {
+        (Some(workspace), Some((day_crate, file))) => workspace.join(day_crate).join(file),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// The command line argument of a day binary that selects its puzzle input,
+/// for day binaries that take further arguments of their own.
+#[derive(Debug, Args)]
+pub struct InputArg {
+    /// The file to read the puzzle input from, or `-` to read it from stdin.
+    /// Defaults to the puzzle input bundled with the day.
+    input: Option<InputSource>,
+}
+
+impl InputArg {
+    /// Read the selected puzzle input, falling back to the puzzle input
+    /// bundled at `bundled`.
+    pub fn read(&self, bundled: impl AsRef<Path>) -> anyhow::Result<String> {
+        match &self.input {
+            Some(source) => source.read(),
+            None => read_bundled(bundled),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+#[command(about = "Solve both parts of an Advent of Code puzzle")]
+struct Cli {
+    #[command(flatten)]
+    input: InputArg,
+}
+
+/// Read the puzzle input selected by the command line arguments of a day
+/// binary, falling back to the puzzle input bundled at `bundled`. Invalid
+/// arguments make the process exit with a usage message.
+pub fn from_args(bundled: impl AsRef<Path>) -> anyhow::Result<String> {
+    Cli::parse().input.read(bundled)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_input_source() {
+        assert_eq!(Ok(InputSource::Stdin), "-".parse());
+        assert_eq!(
+            Ok(InputSource::File(PathBuf::from("puzzle_input.txt"))),
+            "puzzle_input.txt".parse()
+        );
+    }
+
+    #[test]
+    fn test_resolve_bundled() {
+        let path = Path::new("/build/2023/day18/puzzle_input.txt");
+        assert_eq!(path, resolve_bundled(path, None));
+        assert_eq!(
+            Path::new("/home/elf/aoc/2023/day18/puzzle_input.txt"),
+            resolve_bundled(path, Some(Path::new("/home/elf/aoc/2023")))
+        );
+    }
+
+    #[test]
+    fn test_missing_input_is_an_error() {
+        let err = read_bundled("does/not/exist.txt").unwrap_err();
+        assert!(format!("{:#}", err).contains("does/not/exist.txt"));
+
+        let source = InputSource::File(PathBuf::from("does/not/exist.txt"));
+        assert!(source.read().is_err());
+    }
+}
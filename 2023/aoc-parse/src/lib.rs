@@ -0,0 +1,198 @@
+//! A shared error type for malformed puzzle inputs, which points to where in
+//! the puzzle input parsing failed.
+
+use nom::{error::Error as NomError, IResult, Offset};
+use std::{error::Error, fmt, str::FromStr};
+
+/// The maximum number of characters of the offending input that are shown.
+const MAX_SNIPPET_CHARS: usize = 32;
+
+/// An error encountered while parsing (part of) a puzzle input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The 1-based line of the puzzle input in which parsing failed.
+    pub line: usize,
+    /// The 1-based column, counted in characters, at which parsing failed.
+    pub column: usize,
+    /// The offending input, i.e. the rest of the line from `column` on.
+    pub snippet: String,
+    pub message: String,
+}
+
+impl ParseError {
+    /// An error at the byte `offset` into the `input`.
+    pub fn new(input: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        let rest_of_line = input[offset..].lines().next().unwrap_or_default();
+
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            snippet: snippet(rest_of_line),
+            message: message.into(),
+        }
+    }
+
+    /// An error at the start of `rest`, which must be a slice of the `input`.
+    pub fn at(input: &str, rest: &str, message: impl Into<String>) -> Self {
+        Self::new(input, input.offset(rest), message)
+    }
+
+    /// Attribute an error found while parsing a single `line` to the line with
+    /// the 0-based `line_index` of the whole puzzle input.
+    pub fn in_line(mut self, line_index: usize, line: &str) -> Self {
+        if self.snippet.is_empty() && self.column == 1 {
+            self.snippet = snippet(line);
+        }
+        self.line += line_index;
+        self
+    }
+}
+
+/// Shorten `s` to at most `MAX_SNIPPET_CHARS` characters.
+fn snippet(s: &str) -> String {
+    match s.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {} at {:?}",
+            self.line, self.column, self.message, self.snippet
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+impl From<parse_display::ParseError> for ParseError {
+    /// `parse_display` does not report where parsing failed, so the error
+    /// points to the start of the parsed input. Use `ParseError::in_line` to
+    /// attribute it to the line that failed to parse.
+    fn from(err: parse_display::ParseError) -> Self {
+        Self {
+            line: 1,
+            column: 1,
+            snippet: String::new(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ParseError {
+    /// Keep a `ParseError` wrapped by `anyhow`, and otherwise point to the
+    /// start of the parsed input, just like for a `parse_display::ParseError`.
+    fn from(err: anyhow::Error) -> Self {
+        err.downcast().unwrap_or_else(|err: anyhow::Error| Self {
+            line: 1,
+            column: 1,
+            snippet: String::new(),
+            message: format!("{:#}", err),
+        })
+    }
+}
+
+impl<'a> From<(&'a str, nom::Err<NomError<&'a str>>)> for ParseError {
+    /// Convert a nom error, which occurred while parsing the `input`, into an
+    /// error at the position where the failing parser stopped.
+    fn from((input, err): (&'a str, nom::Err<NomError<&'a str>>)) -> Self {
+        match err {
+            nom::Err::Error(err) | nom::Err::Failure(err) => Self::at(
+                input,
+                err.input,
+                format!("expected {}", err.code.description()),
+            ),
+            nom::Err::Incomplete(_) => Self::new(input, input.len(), "unexpected end of input"),
+        }
+    }
+}
+
+/// Run a nom `parser` on the whole `input`, where only whitespace may be left
+/// over.
+pub fn parse_all<'a, T, P>(input: &'a str, mut parser: P) -> Result<T, ParseError>
+where
+    P: FnMut(&'a str) -> IResult<&'a str, T>,
+{
+    let (rest, parsed) = parser(input).map_err(|err| ParseError::from((input, err)))?;
+
+    match rest.trim_start() {
+        "" => Ok(parsed),
+        rest => Err(ParseError::at(input, rest, "unexpected trailing input")),
+    }
+}
+
+/// Parse each line of the `input` with `T::from_str`, and attribute an error
+/// to the line it occurred in.
+pub fn parse_lines<T, C>(input: &str) -> Result<C, ParseError>
+where
+    T: FromStr,
+    ParseError: From<T::Err>,
+    C: FromIterator<T>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(line_index, line)| {
+            line.parse()
+                .map_err(|err| ParseError::from(err).in_line(line_index, line))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nom::{character::complete::digit1, sequence::terminated};
+    use parse_display::FromStr;
+
+    #[derive(Debug, FromStr, PartialEq, Eq)]
+    #[display("{0} {1}")]
+    struct Pair(u32, u32);
+
+    #[test]
+    fn test_position_of_error() {
+        let input = "first line\nsecond line";
+        let err = ParseError::new(input, 18, "oops");
+
+        assert_eq!((2, 8), (err.line, err.column));
+        assert_eq!("line", err.snippet);
+        assert_eq!("line 2, column 8: oops at \"line\"", err.to_string());
+    }
+
+    #[test]
+    fn test_parse_lines() {
+        assert_eq!(
+            Ok(vec![Pair(1, 2), Pair(3, 4)]),
+            parse_lines::<Pair, Vec<_>>("1 2\n3 4")
+        );
+
+        let err = parse_lines::<Pair, Vec<_>>("1 2\n3 4\n5 x").unwrap_err();
+        assert_eq!((3, 1), (err.line, err.column));
+        assert_eq!("5 x", err.snippet);
+    }
+
+    #[test]
+    fn test_parse_all() {
+        let number = |s| terminated(digit1, nom::character::complete::char(';'))(s);
+
+        assert_eq!(Ok("42"), parse_all("42;\n", number));
+
+        let err = parse_all("42;\n7", number).unwrap_err();
+        assert_eq!((2, 1, "7"), (err.line, err.column, err.snippet.as_str()));
+
+        let err = parse_all("42", number).unwrap_err();
+        assert_eq!((1, 3), (err.line, err.column));
+        assert_eq!("expected Char", err.message);
+    }
+
+    #[test]
+    fn test_keep_wrapped_parse_error() {
+        let err = ParseError::new("abc", 1, "oops");
+        assert_eq!(err, ParseError::from(anyhow::Error::from(err.clone())));
+    }
+}
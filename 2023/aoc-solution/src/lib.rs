@@ -0,0 +1,183 @@
+//! The answer to a part of an Advent of Code puzzle, so answers can be
+//! compared and recorded without parsing them out of strings.
+
+use std::{convert::Infallible, fmt, num::TryFromIntError, str::FromStr};
+
+/// The answer to a part of a puzzle.
+///
+/// Integers are compared by their value, no matter which variant they are
+/// stored in, but the numeric `From` conversions always pick `Integer` for
+/// values that fit into an `i64`.
+#[derive(Debug, Clone)]
+pub enum Solution {
+    Integer(i64),
+    BigInteger(i128),
+    String(String),
+    /// Text spanning multiple lines, e.g. letters drawn into a grid.
+    Grid(String),
+}
+
+impl Solution {
+    /// A multi-line answer, given as one line per row.
+    pub fn grid(text: impl Into<String>) -> Self {
+        Solution::Grid(text.into())
+    }
+
+    /// The value of an integer answer.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Solution::Integer(n) => Some(i128::from(*n)),
+            Solution::BigInteger(n) => Some(*n),
+            Solution::String(_) | Solution::Grid(_) => None,
+        }
+    }
+
+    pub fn is_multi_line(&self) -> bool {
+        matches!(self, Solution::Grid(_))
+    }
+}
+
+impl PartialEq for Solution {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Solution::String(a), Solution::String(b)) | (Solution::Grid(a), Solution::Grid(b)) => {
+                a == b
+            }
+            _ => matches!(
+                (self.as_integer(), other.as_integer()),
+                (Some(a), Some(b)) if a == b
+            ),
+        }
+    }
+}
+
+impl Eq for Solution {}
+
+impl fmt::Display for Solution {
+    /// Display the answer the way it is entered on the puzzle's website, with
+    /// grids spanning multiple lines.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Solution::Integer(n) => write!(f, "{}", n),
+            Solution::BigInteger(n) => write!(f, "{}", n),
+            Solution::String(s) | Solution::Grid(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl FromStr for Solution {
+    type Err = Infallible;
+
+    /// Parse a displayed answer back, which is an integer if it is displayed
+    /// exactly like one, a grid if it spans multiple lines, and a string
+    /// otherwise. So e.g. `007` stays a string, which keeps its leading zeros.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<i128>() {
+            Ok(n) if n.to_string() == s => n.into(),
+            _ if s.contains('\n') => Solution::grid(s),
+            _ => s.into(),
+        })
+    }
+}
+
+macro_rules! impl_from_small_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Solution {
+                fn from(n: $integer) -> Self {
+                    Solution::Integer(i64::from(n))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_small_integer!(u8, u16, u32, i8, i16, i32, i64);
+
+macro_rules! impl_from_large_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for Solution {
+                fn from(n: $integer) -> Self {
+                    match i64::try_from(n) {
+                        Ok(n) => Solution::Integer(n),
+                        Err(_) => Solution::BigInteger(
+                            i128::try_from(n).expect("integer should fit into an i128"),
+                        ),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_large_integer!(u64, usize, i128, isize);
+
+impl TryFrom<u128> for Solution {
+    type Error = TryFromIntError;
+
+    /// Fails if the integer does not fit into an `i128`.
+    fn try_from(n: u128) -> Result<Self, Self::Error> {
+        Ok(i128::try_from(n)?.into())
+    }
+}
+
+impl From<String> for Solution {
+    fn from(s: String) -> Self {
+        Solution::String(s)
+    }
+}
+
+impl From<&str> for Solution {
+    fn from(s: &str) -> Self {
+        Solution::String(s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_integers_compare_by_value() {
+        assert_eq!(Solution::Integer(42), 42u8.into());
+        assert_eq!(Solution::Integer(42), 42usize.into());
+        assert_eq!(Solution::Integer(-7), Solution::BigInteger(-7));
+        assert_eq!(
+            Solution::BigInteger(u64::MAX.into()),
+            Solution::from(u64::MAX)
+        );
+        assert_eq!(Ok(Solution::Integer(42)), Solution::try_from(42u128));
+        assert_eq!(
+            Ok(Solution::BigInteger(i128::MAX)),
+            Solution::try_from(i128::MAX as u128)
+        );
+        assert!(Solution::try_from(u128::MAX).is_err());
+        assert_ne!(Solution::Integer(42), "42".into());
+        assert_ne!(Solution::from("a"), Solution::grid("a"));
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for solution in [
+            Solution::from(625243292686usize),
+            Solution::from(i128::MIN),
+            Solution::from(-3),
+            Solution::from("AB-CD"),
+            Solution::grid("#..#\n####\n#..#"),
+        ] {
+            assert_eq!(Ok(solution.clone()), solution.to_string().parse());
+        }
+    }
+
+    #[test]
+    fn test_parse_keeps_non_canonical_integers_as_text() {
+        assert_eq!(Ok(Solution::Integer(7)), "7".parse());
+        assert_eq!(Ok(Solution::Integer(-7)), "-7".parse());
+        for s in ["007", "+7", "-0", "00"] {
+            let solution: Solution = s.parse().unwrap();
+            assert_eq!(Solution::from(s), solution);
+            assert_eq!(s, solution.to_string());
+        }
+    }
+}
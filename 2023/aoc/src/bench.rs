@@ -0,0 +1,192 @@
+//! Benchmarking of solvers: Each solver is run a number of times after some
+//! warm-up runs, and the durations of the parse and solve phases are
+//! summarized separately.
+
+use crate::registry::{Part, Puzzle, RunTimes, Solver};
+use anyhow::Context;
+use aoc_solution::Solution;
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::Write,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// How often a solver is run during a benchmark.
+#[derive(Debug, Clone, Copy)]
+pub struct BenchConfig {
+    /// Runs whose durations are discarded, e.g. to warm up caches.
+    pub warmup_runs: usize,
+    /// Runs whose durations are measured. Must be at least 1.
+    pub runs: usize,
+}
+
+/// Summary statistics over the durations of multiple runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub max: Duration,
+}
+
+impl Stats {
+    /// Summarize a non-empty list of durations.
+    fn new(mut durations: Vec<Duration>) -> Self {
+        assert!(!durations.is_empty(), "there must be at least one duration");
+        durations.sort();
+
+        let len = durations.len();
+        let median = if len.is_multiple_of(2) {
+            (durations[len / 2 - 1] + durations[len / 2]) / 2
+        } else {
+            durations[len / 2]
+        };
+
+        Self {
+            min: durations[0],
+            median,
+            max: durations[len - 1],
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "min {:?}, median {:?}, max {:?}",
+            self.min, self.median, self.max
+        )
+    }
+}
+
+#[test]
+fn test_stats() {
+    let ms = Duration::from_millis;
+
+    let stats = Stats::new(vec![ms(3), ms(1), ms(2)]);
+    assert_eq!((ms(1), ms(2), ms(3)), (stats.min, stats.median, stats.max));
+
+    let stats = Stats::new(vec![ms(4), ms(1), ms(2), ms(8)]);
+    assert_eq!((ms(1), ms(3), ms(8)), (stats.min, stats.median, stats.max));
+}
+
+/// The result of benchmarking one part of a puzzle.
+#[derive(Debug)]
+pub struct Benchmark {
+    pub year: u16,
+    pub day: u8,
+    pub part: Part,
+    pub runs: usize,
+    pub answer: Solution,
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+impl Benchmark {
+    /// The header of the CSV summary written by `Benchmark::append_csv`.
+    const CSV_HEADER: &'static str = "timestamp,year,day,part,runs,\
+        parse_min_ns,parse_median_ns,parse_max_ns,\
+        solve_min_ns,solve_median_ns,solve_max_ns";
+
+    /// Append the benchmarks as CSV rows to the file at `path`, so that
+    /// multiple invocations can be compared over time. The header is only
+    /// written if the file is empty.
+    pub fn append_csv(benchmarks: &[Benchmark], path: &Path) -> anyhow::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("failed to open {}", path.display()))?;
+
+        if file.metadata()?.len() == 0 {
+            writeln!(file, "{}", Self::CSV_HEADER)?;
+        }
+
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        for benchmark in benchmarks {
+            writeln!(file, "{},{}", timestamp, benchmark.csv_row())?;
+        }
+
+        Ok(())
+    }
+
+    fn csv_row(&self) -> String {
+        let Self {
+            year,
+            day,
+            part,
+            runs,
+            parse,
+            solve,
+            ..
+        } = self;
+
+        format!(
+            "{},{},{},{},{},{},{},{},{},{}",
+            year,
+            day,
+            part,
+            runs,
+            parse.min.as_nanos(),
+            parse.median.as_nanos(),
+            parse.max.as_nanos(),
+            solve.min.as_nanos(),
+            solve.median.as_nanos(),
+            solve.max.as_nanos()
+        )
+    }
+}
+
+impl fmt::Display for Benchmark {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} day {:02} part {} ({} runs): {}",
+            self.year, self.day, self.part, self.runs, self.answer
+        )?;
+        writeln!(f, "  parse: {}", self.parse)?;
+        write!(f, "  solve: {}", self.solve)
+    }
+}
+
+/// Benchmark a part of a `puzzle` on the `puzzle_input`. Every run, including
+/// the warm-up runs, must succeed.
+pub fn benchmark<F>(
+    puzzle: &Puzzle,
+    part: Part,
+    puzzle_input: &str,
+    config: BenchConfig,
+    run: F,
+) -> anyhow::Result<Benchmark>
+where
+    F: Fn(Solver, &str) -> anyhow::Result<(Solution, RunTimes)>,
+{
+    let solver = puzzle.solver(part);
+
+    for _ in 0..config.warmup_runs {
+        run(solver, puzzle_input)?;
+    }
+
+    let mut answer = None;
+    let mut parse_times = Vec::with_capacity(config.runs);
+    let mut solve_times = Vec::with_capacity(config.runs);
+
+    for _ in 0..config.runs.max(1) {
+        let (run_answer, run_times) = run(solver, puzzle_input)?;
+        answer.get_or_insert(run_answer);
+        parse_times.push(run_times.parse);
+        solve_times.push(run_times.solve);
+    }
+
+    Ok(Benchmark {
+        year: puzzle.year,
+        day: puzzle.day,
+        part,
+        runs: parse_times.len(),
+        answer: answer.expect("there is at least one measured run"),
+        parse: Stats::new(parse_times),
+        solve: Stats::new(solve_times),
+    })
+}
@@ -0,0 +1,226 @@
+//! Regression checks of solvers against the answers recorded for their bundled
+//! puzzle inputs.
+
+use crate::registry::{Part, Puzzle, Solver};
+use anyhow::{anyhow, Context};
+use aoc_solution::Solution;
+use parse_display::{Display, FromStr};
+use std::{env, fmt};
+
+/// The environment variable that allows parts without a recorded answer,
+/// just like `aoc check --allow-unverified`.
+pub const ALLOW_UNVERIFIED_VAR: &str = "AOC_ALLOW_UNVERIFIED";
+
+/// Whether parts without a recorded answer are allowed by the environment.
+pub fn unverified_allowed_by_env() -> bool {
+    env::var_os(ALLOW_UNVERIFIED_VAR).is_some()
+}
+
+/// A single line of an answers file.
+#[derive(Debug, Display, FromStr)]
+#[display("part {part}: {answer}")]
+struct RecordedAnswer {
+    part: Part,
+    answer: String,
+}
+
+/// The answers recorded in an answers file, where each part's answer is
+/// optional.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct RecordedAnswers {
+    part01: Option<Solution>,
+    part02: Option<Solution>,
+}
+
+impl RecordedAnswers {
+    /// Parse the content of an answers file. Blank lines are ignored, and
+    /// each part may be recorded at most once.
+    pub fn parse(answers: &str) -> anyhow::Result<Self> {
+        let mut recorded_answers = Self::default();
+
+        for (line_index, line) in answers.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let RecordedAnswer { part, answer } = line.parse().with_context(|| {
+                format!(
+                    "line {} of answers file: expected `part <n>: <answer>`, found {:?}",
+                    line_index + 1,
+                    line
+                )
+            })?;
+
+            let slot = match part {
+                Part::One => &mut recorded_answers.part01,
+                Part::Two => &mut recorded_answers.part02,
+            };
+            let Ok(answer) = answer.parse();
+            if slot.replace(answer).is_some() {
+                return Err(anyhow!(
+                    "line {} of answers file: part {} is recorded twice",
+                    line_index + 1,
+                    part
+                ));
+            }
+        }
+
+        Ok(recorded_answers)
+    }
+
+    pub fn get(&self, part: Part) -> Option<&Solution> {
+        match part {
+            Part::One => self.part01.as_ref(),
+            Part::Two => self.part02.as_ref(),
+        }
+    }
+}
+
+#[test]
+fn test_parse_recorded_answers() -> anyhow::Result<()> {
+    let recorded_answers = RecordedAnswers::parse("part 1: 42\n\npart 2: a: b\n")?;
+    assert_eq!(Some(&Solution::from(42)), recorded_answers.get(Part::One));
+    assert_eq!(
+        Some(&Solution::from("a: b")),
+        recorded_answers.get(Part::Two)
+    );
+
+    let recorded_answers = RecordedAnswers::parse("part 2: 7\n")?;
+    assert_eq!(None, recorded_answers.get(Part::One));
+
+    assert!(RecordedAnswers::parse("part 1: 1\npart 1: 2\n").is_err());
+    assert!(RecordedAnswers::parse("42\n").is_err());
+
+    Ok(())
+}
+
+/// The outcome of checking a solver against its recorded answer.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Correct {
+        answer: Solution,
+    },
+    Wrong {
+        expected: Solution,
+        actual: Solution,
+    },
+    Unverified {
+        actual: Solution,
+    },
+}
+
+impl Verdict {
+    pub fn is_wrong(&self) -> bool {
+        matches!(self, Verdict::Wrong { .. })
+    }
+
+    pub fn is_unverified(&self) -> bool {
+        matches!(self, Verdict::Unverified { .. })
+    }
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Correct { answer } => write!(f, "ok ({})", answer),
+            Verdict::Wrong { expected, actual } => {
+                write!(f, "WRONG (expected {}, got {})", expected, actual)
+            }
+            Verdict::Unverified { actual } => {
+                write!(f, "unverified, no answer recorded (got {})", actual)
+            }
+        }
+    }
+}
+
+/// Solve a part of a `puzzle` on its bundled `puzzle_input` with `run`, and
+/// compare the answer to the recorded one.
+pub fn check<F>(puzzle: &Puzzle, part: Part, puzzle_input: &str, run: F) -> anyhow::Result<Verdict>
+where
+    F: FnOnce(Solver, &str) -> anyhow::Result<Solution>,
+{
+    let recorded_answers = RecordedAnswers::parse(puzzle.answers)?;
+    let actual = run(puzzle.solver(part), puzzle_input)?;
+
+    let verdict = match recorded_answers.get(part) {
+        Some(expected) if *expected == actual => Verdict::Correct { answer: actual },
+        Some(expected) => Verdict::Wrong {
+            expected: expected.clone(),
+            actual,
+        },
+        None => Verdict::Unverified { actual },
+    };
+
+    Ok(verdict)
+}
+
+/// Check every registered puzzle against its recorded answers, just like
+/// `aoc check`. This solves every real puzzle input, so it only runs in
+/// release mode, i.e. `cargo test --release -p aoc`.
+///
+/// A part without a recorded answer fails the test, unless the environment
+/// variable `AOC_ALLOW_UNVERIFIED` is set, e.g. while a new day is in
+/// progress.
+#[test]
+#[cfg_attr(
+    debug_assertions,
+    ignore = "solves every real puzzle input, run it with `cargo test --release`"
+)]
+fn test_recorded_answers() -> std::thread::Result<()> {
+    use crate::registry::PUZZLES;
+    use std::io::Write;
+    use strum::IntoEnumIterator;
+
+    // Some solvers recurse deeply on the real puzzle inputs, so give them the
+    // stack size of a main thread rather than the smaller one of test threads.
+    const STACK_SIZE: usize = 8 * 1024 * 1024;
+
+    let allow_unverified = unverified_allowed_by_env();
+
+    let check_all = move || {
+        let mut failures = Vec::new();
+        let mut unverified = Vec::new();
+
+        for puzzle in PUZZLES {
+            let puzzle_input = match aoc_input::read_bundled(puzzle.bundled_puzzle_input) {
+                Ok(puzzle_input) => puzzle_input,
+                Err(err) => {
+                    failures.push(format!("{}: error: {:#}", puzzle.label_without_part(), err));
+                    continue;
+                }
+            };
+
+            for part in Part::iter() {
+                let label = puzzle.label(part);
+                match check(puzzle, part, &puzzle_input, |solver, puzzle_input| {
+                    solver(puzzle_input).map(|(answer, _)| answer)
+                }) {
+                    Ok(verdict @ Verdict::Unverified { .. }) => {
+                        unverified.push(format!("{}: {}", label, verdict))
+                    }
+                    Ok(verdict) if verdict.is_wrong() => {
+                        failures.push(format!("{}: {}", label, verdict))
+                    }
+                    Ok(_) => {}
+                    Err(err) => failures.push(format!("{}: error: {:#}", label, err)),
+                }
+            }
+        }
+
+        if !allow_unverified {
+            failures.extend(unverified.iter().cloned());
+        }
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+        // Write to stderr directly, since libtest would capture `eprintln!`
+        // and only show it for failing tests.
+        for unverified in &unverified {
+            let _ = writeln!(std::io::stderr(), "{}", unverified);
+        }
+    };
+
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(check_all)
+        .expect("failed to spawn thread")
+        .join()
+}
@@ -0,0 +1,424 @@
+//! A single runner for all registered Advent of Code puzzles, e.g.
+//! `aoc run 2023 14 --part 2 --input path/to/puzzle_input.txt`,
+//! `aoc bench 2023 14 --runs 20 --output benchmarks.csv` or `aoc check 2023`.
+//! New days are created with e.g. `aoc new 20 --dep grid --example example.txt`.
+
+mod bench;
+mod check;
+mod registry;
+mod scaffold;
+
+use anyhow::anyhow;
+use aoc_input::InputSource;
+use aoc_solution::Solution;
+use bench::{BenchConfig, Benchmark};
+use clap::{Args, Parser, Subcommand};
+use registry::{Day, Part, Puzzle, RunTimes, Solver, Year};
+use std::{borrow::Cow, panic, path::PathBuf, process::ExitCode};
+use strum::IntoEnumIterator;
+
+const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0  all selected puzzle parts were solved
+  1  at least one solver returned an error, or (for `check`) a wrong answer or,
+     unless unverified parts are allowed, a part without a recorded answer
+  2  invalid usage, e.g. no puzzle is registered for the selected year and day, or
+     (for `new`) the day already exists
+  3  the puzzle input could not be read, the benchmark summary could not be written,
+     or (for `new`) the day crate could not be created";
+
+#[derive(Debug, Parser)]
+#[command(about = "Run Advent of Code puzzle solutions", after_help = EXIT_CODES_HELP)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Solve puzzles and print one answer per line.
+    Run(RunArgs),
+
+    /// Solve puzzles repeatedly and report how long parsing and solving take.
+    Bench(BenchArgs),
+
+    /// Solve puzzles on their bundled puzzle inputs and compare the answers to
+    /// the recorded ones.
+    Check(CheckArgs),
+
+    /// Create a new day crate in the workspace from its `template` member.
+    New(NewArgs),
+}
+
+#[derive(Debug, Args)]
+struct PuzzleSelection {
+    /// The year of the puzzles.
+    year: Year,
+
+    /// The day of the puzzle. All registered days of the year are selected if
+    /// omitted.
+    day: Option<Day>,
+
+    /// The part of the puzzle. Both parts are selected if omitted.
+    #[arg(long)]
+    part: Option<Part>,
+}
+
+#[derive(Debug, Args)]
+struct InputArg {
+    /// Read the puzzle input from this file, or from stdin if `-`, instead of
+    /// using the bundled one. The bundled puzzle inputs are read from the
+    /// workspace the runner was built in, or from `$AOC_WORKSPACE` if set.
+    #[arg(long, requires = "day")]
+    input: Option<InputSource>,
+}
+
+#[derive(Debug, Args)]
+struct RunArgs {
+    #[command(flatten)]
+    selection: PuzzleSelection,
+
+    #[command(flatten)]
+    input: InputArg,
+}
+
+#[derive(Debug, Args)]
+struct BenchArgs {
+    #[command(flatten)]
+    selection: PuzzleSelection,
+
+    #[command(flatten)]
+    input: InputArg,
+
+    /// The number of measured runs per puzzle part.
+    #[arg(long, default_value_t = 10, value_parser = clap::value_parser!(u32).range(1..))]
+    runs: u32,
+
+    /// The number of unmeasured runs per puzzle part before the measured
+    /// ones.
+    #[arg(long, default_value_t = 3)]
+    warmup: u32,
+
+    /// Append a CSV summary of the benchmarks to this file.
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct CheckArgs {
+    #[command(flatten)]
+    selection: PuzzleSelection,
+
+    /// Do not fail on parts without a recorded answer, e.g. while a new day
+    /// is in progress. Also enabled by setting `AOC_ALLOW_UNVERIFIED`.
+    #[arg(long)]
+    allow_unverified: bool,
+}
+
+#[derive(Debug, Args)]
+struct NewArgs {
+    /// The day of the puzzle, which names the crate, e.g. `day07` for 7.
+    #[arg(value_parser = clap::value_parser!(Day).range(1..=25))]
+    day: Day,
+
+    /// Workspace dependencies to add to the ones of the template, e.g.
+    /// `grid,nom,rayon`. The `aoc-` prefix of the helper crates may be
+    /// omitted.
+    #[arg(long = "dep", value_delimiter = ',')]
+    dependencies: Vec<String>,
+
+    /// Pre-fill the example puzzle input of both parts with the content of
+    /// this file.
+    #[arg(long)]
+    example: Option<InputSource>,
+
+    /// The workspace to create the day in.
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/.."))]
+    workspace: PathBuf,
+}
+
+/// The outcome of a runner invocation, reported as its exit code. `clap`
+/// already exits with `Status::Usage` on invalid arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Success = 0,
+    SolveFailed = 1,
+    Usage = 2,
+    IoFailed = 3,
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status as u8)
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let status = match cli.command {
+        Command::Run(args) => run(&args),
+        Command::Bench(args) => bench(&args),
+        Command::Check(args) => check(&args),
+        Command::New(args) => new(&args),
+    };
+
+    status.into()
+}
+
+/// The puzzles and parts chosen by a `PuzzleSelection`, together with the
+/// custom puzzle input, if any.
+struct Selected {
+    puzzles: Vec<&'static Puzzle>,
+    parts: Vec<Part>,
+    custom_puzzle_input: Option<String>,
+}
+
+impl Selected {
+    /// The puzzle input a `puzzle` should be solved with, reporting a missing
+    /// bundled puzzle input to stderr.
+    fn puzzle_input(&self, puzzle: &Puzzle) -> Result<Cow<'_, str>, Status> {
+        match &self.custom_puzzle_input {
+            Some(puzzle_input) => Ok(Cow::Borrowed(puzzle_input)),
+            None => match aoc_input::read_bundled(puzzle.bundled_puzzle_input) {
+                Ok(puzzle_input) => Ok(Cow::Owned(puzzle_input)),
+                Err(err) => {
+                    eprintln!("error: {}: {:#}", puzzle.label_without_part(), err);
+                    Err(Status::IoFailed)
+                }
+            },
+        }
+    }
+}
+
+impl PuzzleSelection {
+    /// Resolve the selection and read the custom puzzle input from `input`,
+    /// reporting any problem to stderr.
+    fn resolve(&self, input: Option<&InputSource>) -> Result<Selected, Status> {
+        let puzzles: Vec<&Puzzle> = registry::find(self.year, self.day).collect();
+        if puzzles.is_empty() {
+            match self.day {
+                Some(day) => eprintln!("error: no puzzle registered for {} day {}", self.year, day),
+                None => eprintln!("error: no puzzles registered for {}", self.year),
+            }
+            return Err(Status::Usage);
+        }
+
+        let custom_puzzle_input = match input {
+            Some(source) => match source.read() {
+                Ok(puzzle_input) => Some(puzzle_input),
+                Err(err) => {
+                    eprintln!("error: {:#}", err);
+                    return Err(Status::IoFailed);
+                }
+            },
+            None => None,
+        };
+
+        let parts = match self.part {
+            Some(part) => vec![part],
+            None => Part::iter().collect(),
+        };
+
+        Ok(Selected {
+            puzzles,
+            parts,
+            custom_puzzle_input,
+        })
+    }
+}
+
+/// Run a `solver`, turning a panic into an error. The panic message has
+/// already been printed by the panic hook.
+fn run_solver(solver: Solver, puzzle_input: &str) -> anyhow::Result<(Solution, RunTimes)> {
+    panic::catch_unwind(|| solver(puzzle_input)).unwrap_or_else(|_| Err(anyhow!("solver panicked")))
+}
+
+/// Solve all selected puzzle parts. Answers are printed to stdout, errors to
+/// stderr, and a failing part (even one that panics) does not prevent the
+/// remaining parts from running.
+fn run(args: &RunArgs) -> Status {
+    let selected = match args.selection.resolve(args.input.input.as_ref()) {
+        Ok(selected) => selected,
+        Err(status) => return status,
+    };
+
+    let mut status = Status::Success;
+
+    for &puzzle in &selected.puzzles {
+        let puzzle_input = match selected.puzzle_input(puzzle) {
+            Ok(puzzle_input) => puzzle_input,
+            Err(puzzle_status) => {
+                status = puzzle_status;
+                continue;
+            }
+        };
+
+        for &part in &selected.parts {
+            let label = puzzle.label(part);
+
+            match run_solver(puzzle.solver(part), &puzzle_input) {
+                // Start multi-line answers on their own line, so that they
+                // are not misaligned by the label.
+                Ok((answer, _)) if answer.is_multi_line() => println!("{}:\n{}", label, answer),
+                Ok((answer, _)) => println!("{}: {}", label, answer),
+                Err(err) => {
+                    eprintln!("error: {}: {:#}", label, err);
+                    status = Status::SolveFailed;
+                }
+            }
+        }
+    }
+
+    status
+}
+
+/// Benchmark all selected puzzle parts. A human-readable summary is printed to
+/// stdout, and optionally appended as CSV to a file. A failing part does not
+/// prevent the remaining parts from being benchmarked.
+fn bench(args: &BenchArgs) -> Status {
+    let selected = match args.selection.resolve(args.input.input.as_ref()) {
+        Ok(selected) => selected,
+        Err(status) => return status,
+    };
+
+    let config = BenchConfig {
+        warmup_runs: args.warmup as usize,
+        runs: args.runs as usize,
+    };
+
+    let mut status = Status::Success;
+    let mut benchmarks = Vec::new();
+
+    for &puzzle in &selected.puzzles {
+        let puzzle_input = match selected.puzzle_input(puzzle) {
+            Ok(puzzle_input) => puzzle_input,
+            Err(puzzle_status) => {
+                status = puzzle_status;
+                continue;
+            }
+        };
+
+        for &part in &selected.parts {
+            match bench::benchmark(puzzle, part, &puzzle_input, config, run_solver) {
+                Ok(benchmark) => {
+                    println!("{}", benchmark);
+                    benchmarks.push(benchmark);
+                }
+                Err(err) => {
+                    eprintln!("error: {}: {:#}", puzzle.label(part), err);
+                    status = Status::SolveFailed;
+                }
+            }
+        }
+    }
+
+    if let Some(path) = &args.output {
+        if let Err(err) = Benchmark::append_csv(&benchmarks, path) {
+            eprintln!("error: {:#}", err);
+            return Status::IoFailed;
+        }
+    }
+
+    status
+}
+
+/// Check all selected puzzle parts against their recorded answers. Parts
+/// without a recorded answer are reported as unverified, and fail the check
+/// unless they are allowed.
+fn check(args: &CheckArgs) -> Status {
+    let allow_unverified = args.allow_unverified || check::unverified_allowed_by_env();
+    let selected = match args.selection.resolve(None) {
+        Ok(selected) => selected,
+        Err(status) => return status,
+    };
+
+    let mut status = Status::Success;
+    let mut unverified = 0;
+
+    for &puzzle in &selected.puzzles {
+        let puzzle_input = match selected.puzzle_input(puzzle) {
+            Ok(puzzle_input) => puzzle_input,
+            Err(puzzle_status) => {
+                status = puzzle_status;
+                continue;
+            }
+        };
+
+        for &part in &selected.parts {
+            let label = puzzle.label(part);
+
+            match check::check(puzzle, part, &puzzle_input, |solver, puzzle_input| {
+                run_solver(solver, puzzle_input).map(|(answer, _)| answer)
+            }) {
+                Ok(verdict) => {
+                    println!("{}: {}", label, verdict);
+                    if verdict.is_wrong() {
+                        status = Status::SolveFailed;
+                    } else if verdict.is_unverified() {
+                        unverified += 1;
+                    }
+                }
+                Err(err) => {
+                    eprintln!("error: {}: {:#}", label, err);
+                    status = Status::SolveFailed;
+                }
+            }
+        }
+    }
+
+    if unverified > 0 && allow_unverified {
+        eprintln!(
+            "warning: {} puzzle part(s) have no recorded answer",
+            unverified
+        );
+    } else if unverified > 0 {
+        eprintln!(
+            "error: {} puzzle part(s) have no recorded answer; pass --allow-unverified \
+            or set {} to allow them",
+            unverified,
+            check::ALLOW_UNVERIFIED_VAR
+        );
+        status = Status::SolveFailed;
+    }
+
+    status
+}
+
+/// Create a new day crate, which is picked up by the workspace automatically.
+fn new(args: &NewArgs) -> Status {
+    let example = match &args.example {
+        Some(source) => match source.read() {
+            Ok(example) => Some(example),
+            Err(err) => {
+                eprintln!("error: {:#}", err);
+                return Status::IoFailed;
+            }
+        },
+        None => None,
+    };
+
+    let new_day = scaffold::NewDay {
+        day: args.day,
+        dependencies: args.dependencies.clone(),
+        example,
+    };
+
+    match scaffold::create(&args.workspace, &new_day) {
+        Ok(day_dir) => {
+            println!("created {}", day_dir.display());
+            eprintln!(
+                "note: save the puzzle input to {}, and register the day in aoc/src/registry.rs",
+                day_dir.join("puzzle_input.txt").display()
+            );
+            Status::Success
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            match err {
+                scaffold::ScaffoldError::Io(_) => Status::IoFailed,
+                _ => Status::Usage,
+            }
+        }
+    }
+}
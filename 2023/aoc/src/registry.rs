@@ -0,0 +1,186 @@
+//! The registry of all puzzles the runner knows how to solve.
+
+use aoc_solution::Solution;
+use parse_display::{Display, FromStr};
+use std::time::{Duration, Instant};
+use strum::EnumIter;
+
+pub type Year = u16;
+pub type Day = u8;
+
+/// A function that solves one part of a puzzle, given the puzzle input. It
+/// returns the answer together with the time spent in each phase.
+pub type Solver = fn(&str) -> anyhow::Result<(Solution, RunTimes)>;
+
+/// The wall-clock time spent in each phase of a single solver run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunTimes {
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+/// Run `parse` and then `solve` on the `puzzle_input`, and measure both
+/// phases separately.
+pub fn run_timed<I, P, S>(
+    puzzle_input: &str,
+    parse: P,
+    solve: S,
+) -> anyhow::Result<(Solution, RunTimes)>
+where
+    P: FnOnce(&str) -> anyhow::Result<I>,
+    S: FnOnce(I) -> anyhow::Result<Solution>,
+{
+    let parse_start = Instant::now();
+    let parsed = parse(puzzle_input)?;
+    let parse_time = parse_start.elapsed();
+
+    let solve_start = Instant::now();
+    let answer = solve(parsed)?;
+    let solve_time = solve_start.elapsed();
+
+    let run_times = RunTimes {
+        parse: parse_time,
+        solve: solve_time,
+    };
+    Ok((answer, run_times))
+}
+
+/// One of the two parts every puzzle consists of.
+#[derive(Debug, Display, FromStr, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, EnumIter)]
+pub enum Part {
+    #[display("1")]
+    One,
+
+    #[display("2")]
+    Two,
+}
+
+/// A puzzle of a single day, together with the path of its bundled puzzle
+/// input, the answers recorded for it and the solvers of both of its parts.
+pub struct Puzzle {
+    pub year: Year,
+    pub day: Day,
+    pub bundled_puzzle_input: &'static str,
+    pub answers: &'static str,
+    part01: Solver,
+    part02: Solver,
+}
+
+impl Puzzle {
+    pub fn solver(&self, part: Part) -> Solver {
+        match part {
+            Part::One => self.part01,
+            Part::Two => self.part02,
+        }
+    }
+
+    /// A human-readable name of this puzzle.
+    pub fn label_without_part(&self) -> String {
+        format!("{} day {:02}", self.year, self.day)
+    }
+
+    /// A human-readable name of a part of this puzzle.
+    pub fn label(&self, part: Part) -> String {
+        format!("{} part {}", self.label_without_part(), part)
+    }
+}
+
+/// Register the puzzle of a day crate whose `part01` and `part02` modules
+/// both provide a `parse` and a `solve_parsed` function that only take the
+/// puzzle input and the parsed puzzle input, respectively.
+macro_rules! puzzle {
+    ($year:literal, $day:literal, $day_crate:ident) => {
+        Puzzle {
+            year: $year,
+            day: $day,
+            bundled_puzzle_input: $day_crate::BUNDLED_PUZZLE_INPUT,
+            answers: $day_crate::ANSWERS,
+            part01: |puzzle_input| {
+                run_timed(
+                    puzzle_input,
+                    $day_crate::part01::parse,
+                    $day_crate::part01::solve_parsed,
+                )
+            },
+            part02: |puzzle_input| {
+                run_timed(
+                    puzzle_input,
+                    $day_crate::part02::parse,
+                    $day_crate::part02::solve_parsed,
+                )
+            },
+        }
+    };
+}
+
+/// All registered puzzles, ordered by year and day.
+pub const PUZZLES: &[Puzzle] = &[
+    puzzle!(2023, 1, day01),
+    puzzle!(2023, 2, day02),
+    puzzle!(2023, 3, day03),
+    puzzle!(2023, 4, day04),
+    puzzle!(2023, 5, day05),
+    puzzle!(2023, 6, day06),
+    puzzle!(2023, 7, day07),
+    puzzle!(2023, 8, day08),
+    puzzle!(2023, 9, day09),
+    puzzle!(2023, 10, day10),
+    Puzzle {
+        year: 2023,
+        day: 11,
+        bundled_puzzle_input: day11::BUNDLED_PUZZLE_INPUT,
+        answers: day11::ANSWERS,
+        part01: |puzzle_input| {
+            run_timed(
+                puzzle_input,
+                day11::part01::parse,
+                day11::part01::solve_parsed,
+            )
+        },
+        part02: |puzzle_input| {
+            run_timed(puzzle_input, day11::part02::parse, |space_grid| {
+                day11::part02::solve_parsed(space_grid, day11::part02::EXPANSION_FACTOR)
+            })
+        },
+    },
+    Puzzle {
+        year: 2023,
+        day: 12,
+        bundled_puzzle_input: day12::BUNDLED_PUZZLE_INPUT,
+        answers: day12::ANSWERS,
+        part01: |puzzle_input| {
+            run_timed(
+                puzzle_input,
+                day12::part01::parse,
+                day12::part01::solve_parsed,
+            )
+        },
+        part02: |puzzle_input| {
+            run_timed(puzzle_input, day12::part02::parse, |lines| {
+                day12::part02::solve_parsed(lines, day12::part02::UNFOLDING_FACTOR)
+            })
+        },
+    },
+    puzzle!(2023, 13, day13),
+    puzzle!(2023, 14, day14),
+    puzzle!(2023, 15, day15),
+    puzzle!(2023, 16, day16),
+    puzzle!(2023, 17, day17),
+    puzzle!(2023, 18, day18),
+    puzzle!(2023, 19, day19),
+];
+
+/// Find all registered puzzles of a `year`, optionally restricted to a
+/// single `day`.
+pub fn find(year: Year, day: Option<Day>) -> impl Iterator<Item = &'static Puzzle> {
+    PUZZLES
+        .iter()
+        .filter(move |puzzle| puzzle.year == year && day.is_none_or(|day| puzzle.day == day))
+}
+
+#[test]
+fn test_puzzles_are_ordered_and_unique() {
+    assert!(PUZZLES
+        .windows(2)
+        .all(|pair| (pair[0].year, pair[0].day) < (pair[1].year, pair[1].day)));
+}
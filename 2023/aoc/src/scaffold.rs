@@ -0,0 +1,372 @@
+//! Scaffolding of new day crates from the `template` workspace member.
+
+use crate::registry::Day;
+use anyhow::{anyhow, Context};
+use std::{
+    collections::BTreeSet,
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+/// The files of the template that make up a day crate, relative to its root.
+const TEMPLATE_FILES: &[&str] = &[
+    ".gitignore",
+    "Cargo.toml",
+    "answers.txt",
+    "src/lib.rs",
+    "src/main.rs",
+    "src/part01.rs",
+    "src/part02.rs",
+];
+
+/// The files that are always created empty, no matter what the template
+/// contains.
+const EMPTY_FILES: &[&str] = &["puzzle_input.txt"];
+
+/// The line of the template's `example_details()` that is replaced by the
+/// lines of the example puzzle input.
+const EXAMPLE_PLACEHOLDER: &str = "            puzzle input lines";
+
+/// Why a new day crate could not be created.
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// The directory of the day already exists, and is never overwritten.
+    DayExists(PathBuf),
+    /// A requested dependency is not one of the workspace dependencies.
+    UnknownDependency(String),
+    /// Reading the template or writing the new day crate failed.
+    Io(anyhow::Error),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaffoldError::DayExists(path) => {
+                write!(
+                    f,
+                    "{} already exists, refusing to overwrite it",
+                    path.display()
+                )
+            }
+            ScaffoldError::UnknownDependency(dependency) => write!(
+                f,
+                "unknown dependency {:?}, expected one of the workspace dependencies",
+                dependency
+            ),
+            ScaffoldError::Io(err) => write!(f, "{:#}", err),
+        }
+    }
+}
+
+impl From<anyhow::Error> for ScaffoldError {
+    fn from(err: anyhow::Error) -> Self {
+        ScaffoldError::Io(err)
+    }
+}
+
+/// What a new day crate should look like.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewDay {
+    pub day: Day,
+    /// Workspace dependencies to add on top of the template's ones, either
+    /// by their full name (e.g. `aoc-grid`) or without the `aoc-` prefix.
+    pub dependencies: Vec<String>,
+    /// The example puzzle input to pre-fill `example_details()` with.
+    pub example: Option<String>,
+}
+
+impl NewDay {
+    /// The name of both the package and the directory of the day crate.
+    pub fn crate_name(&self) -> String {
+        format!("day{:02}", self.day)
+    }
+}
+
+/// Create the day crate described by `new_day` in the `workspace` directory,
+/// based on the `template` member of that workspace, and return its path.
+pub fn create(workspace: &Path, new_day: &NewDay) -> Result<PathBuf, ScaffoldError> {
+    let crate_name = new_day.crate_name();
+    let day_dir = workspace.join(&crate_name);
+    if day_dir.exists() {
+        return Err(ScaffoldError::DayExists(day_dir));
+    }
+
+    let workspace_manifest = read(&workspace.join("Cargo.toml"))?;
+    let available = workspace_dependencies(&workspace_manifest);
+    let dependencies = new_day
+        .dependencies
+        .iter()
+        .map(|dependency| resolve_dependency(dependency, &available))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Render all files before writing any, so that a broken template does not
+    // leave a half-created day behind.
+    let template_dir = workspace.join("template");
+    let mut files = Vec::new();
+    for &file in TEMPLATE_FILES {
+        let content = read(&template_dir.join(file))?;
+        let content = match file {
+            "Cargo.toml" => render_manifest(&content, &crate_name, &dependencies)?,
+            "answers.txt" => String::new(),
+            "src/main.rs" => content.replace("use template::", &format!("use {}::", crate_name)),
+            "src/part01.rs" | "src/part02.rs" => match &new_day.example {
+                Some(example) => fill_example(&content, example)
+                    .with_context(|| format!("failed to pre-fill the example of {}", file))?,
+                None => content,
+            },
+            _ => content,
+        };
+        files.push((file, content));
+    }
+    files.extend(EMPTY_FILES.iter().map(|&file| (file, String::new())));
+
+    // `create_dir` rather than `create_dir_all`, so that a day created in the
+    // meantime is not overwritten either.
+    fs::create_dir(&day_dir)
+        .with_context(|| format!("failed to create {}", day_dir.display()))
+        .map_err(|err| match day_dir.exists() {
+            true => ScaffoldError::DayExists(day_dir.clone()),
+            false => ScaffoldError::Io(err),
+        })?;
+    for (file, content) in files {
+        let path = day_dir.join(file);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, content).with_context(|| format!("failed to write {}", path.display()))?;
+    }
+
+    Ok(day_dir)
+}
+
+fn read(path: &Path) -> anyhow::Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// The names of the `[workspace.dependencies]` of a workspace manifest.
+fn workspace_dependencies(manifest: &str) -> BTreeSet<&str> {
+    manifest
+        .lines()
+        .skip_while(|line| line.trim() != "[workspace.dependencies]")
+        .skip(1)
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .filter_map(|line| line.split_once('=').map(|(name, _)| name.trim()))
+        .filter(|name| !name.is_empty() && !name.starts_with('#'))
+        .collect()
+}
+
+/// Resolve a requested `dependency` to the name of a workspace dependency,
+/// allowing the `aoc-` prefix of the helper crates to be omitted.
+fn resolve_dependency(
+    dependency: &str,
+    available: &BTreeSet<&str>,
+) -> Result<String, ScaffoldError> {
+    [dependency.to_string(), format!("aoc-{}", dependency)]
+        .into_iter()
+        .find(|name| available.contains(name.as_str()))
+        .ok_or_else(|| ScaffoldError::UnknownDependency(dependency.to_string()))
+}
+
+/// Rename the package of the template's `manifest` to `crate_name`, and add
+/// the `dependencies` to its sorted `[dependencies]`.
+fn render_manifest(
+    manifest: &str,
+    crate_name: &str,
+    dependencies: &[String],
+) -> anyhow::Result<String> {
+    let (package, template_dependencies) = manifest
+        .split_once("[dependencies]\n")
+        .ok_or_else(|| anyhow!("the template manifest has no [dependencies] section"))?;
+
+    let package_name = "name = \"template\"";
+    if !package.contains(package_name) {
+        return Err(anyhow!("the template manifest is not named \"template\""));
+    }
+    let package = package.replacen(package_name, &format!("name = \"{}\"", crate_name), 1);
+
+    let dependency_lines: BTreeSet<String> = template_dependencies
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .chain(
+            dependencies
+                .iter()
+                .map(|dependency| format!("{} = {{ workspace = true }}", dependency)),
+        )
+        .collect();
+
+    let mut manifest = package;
+    manifest.push_str("[dependencies]\n");
+    for line in dependency_lines {
+        manifest.push_str(&line);
+        manifest.push('\n');
+    }
+    Ok(manifest)
+}
+
+/// Replace the placeholder puzzle input of the template's `example_details()`
+/// in a `part` module by the `example` puzzle input.
+fn fill_example(part: &str, example: &str) -> anyhow::Result<String> {
+    let indent = " ".repeat(EXAMPLE_PLACEHOLDER.len() - EXAMPLE_PLACEHOLDER.trim_start().len());
+    let example_lines: Vec<String> = example
+        .lines()
+        .map(|line| {
+            // The example ends up inside of a string literal.
+            let line = line.replace('\\', "\\\\").replace('"', "\\\"");
+            match line.is_empty() {
+                true => line,
+                false => format!("{}{}", indent, line),
+            }
+        })
+        .collect();
+
+    let mut lines: Vec<&str> = part.lines().collect();
+    let placeholder = lines
+        .iter()
+        .position(|&line| line == EXAMPLE_PLACEHOLDER)
+        .ok_or_else(|| anyhow!("the template has no example placeholder"))?;
+    lines.splice(
+        placeholder..=placeholder,
+        example_lines.iter().map(String::as_str),
+    );
+
+    let mut part = lines.join("\n");
+    part.push('\n');
+    Ok(part)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const WORKSPACE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/..");
+
+    #[test]
+    fn test_resolve_dependency() -> anyhow::Result<()> {
+        let manifest = read(&Path::new(WORKSPACE).join("Cargo.toml"))?;
+        let available = workspace_dependencies(&manifest);
+
+        assert_eq!("aoc-grid", resolve_dependency("grid", &available).unwrap());
+        assert_eq!(
+            "aoc-grid",
+            resolve_dependency("aoc-grid", &available).unwrap()
+        );
+        assert_eq!("nom", resolve_dependency("nom", &available).unwrap());
+        assert!(matches!(
+            resolve_dependency("serde", &available),
+            Err(ScaffoldError::UnknownDependency(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_manifest() -> anyhow::Result<()> {
+        let manifest = indoc_manifest(&[
+            "[package]",
+            "name = \"template\"",
+            "",
+            "[dependencies]",
+            "anyhow = { workspace = true }",
+            "itertools = { workspace = true }",
+        ]);
+        let expected = indoc_manifest(&[
+            "[package]",
+            "name = \"day07\"",
+            "",
+            "[dependencies]",
+            "anyhow = { workspace = true }",
+            "aoc-grid = { workspace = true }",
+            "itertools = { workspace = true }",
+            "nom = { workspace = true }",
+        ]);
+
+        let dependencies = [
+            "nom".to_string(),
+            "aoc-grid".to_string(),
+            "anyhow".to_string(),
+        ];
+        assert_eq!(
+            expected,
+            render_manifest(&manifest, "day07", &dependencies)?
+        );
+
+        Ok(())
+    }
+
+    fn indoc_manifest(lines: &[&str]) -> String {
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+
+    #[test]
+    fn test_fill_example() -> anyhow::Result<()> {
+        let part = format!(
+            "let puzzle_input = indoc! {{\"\n{}\n\"}};\n",
+            EXAMPLE_PLACEHOLDER
+        );
+        let expected =
+            "let puzzle_input = indoc! {\"\n            a \\\"b\\\"\n\n            c\\\\\n\"};\n";
+
+        assert_eq!(expected, fill_example(&part, "a \"b\"\n\nc\\\n")?);
+        assert!(fill_example("no placeholder", "a").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_day() -> anyhow::Result<()> {
+        // Create the day in a scratch copy of the workspace that only contains
+        // what scaffolding needs.
+        let workspace = std::env::temp_dir().join(format!("aoc-scaffold-{}", std::process::id()));
+        fs::create_dir_all(workspace.join("template/src"))?;
+        fs::copy(
+            Path::new(WORKSPACE).join("Cargo.toml"),
+            workspace.join("Cargo.toml"),
+        )?;
+        for file in TEMPLATE_FILES {
+            fs::copy(
+                Path::new(WORKSPACE).join("template").join(file),
+                workspace.join("template").join(file),
+            )?;
+        }
+
+        let new_day = NewDay {
+            day: 20,
+            dependencies: vec!["grid".to_string(), "rayon".to_string()],
+            example: Some("#.#\n...\n".to_string()),
+        };
+        let day_dir = create(&workspace, &new_day).map_err(|err| anyhow!("{}", err))?;
+        let second_attempt = create(&workspace, &new_day);
+
+        let manifest = read(&day_dir.join("Cargo.toml"))?;
+        let main = read(&day_dir.join("src/main.rs"))?;
+        let part02 = read(&day_dir.join("src/part02.rs"))?;
+        let puzzle_input = read(&day_dir.join("puzzle_input.txt"))?;
+        fs::remove_dir_all(&workspace)?;
+
+        assert!(manifest.contains("name = \"day20\""));
+        assert!(manifest.contains("aoc-grid = { workspace = true }"));
+        assert!(manifest.contains("rayon = { workspace = true }"));
+        assert!(main.starts_with("use day20::{"));
+        assert!(part02.contains("            #.#\n            ...\n        \"};"));
+        assert!(puzzle_input.is_empty());
+        assert!(matches!(second_attempt, Err(ScaffoldError::DayExists(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_create_refuses_to_overwrite_existing_day() {
+        let new_day = NewDay {
+            day: 1,
+            dependencies: Vec::new(),
+            example: None,
+        };
+
+        assert!(matches!(
+            create(Path::new(WORKSPACE), &new_day),
+            Err(ScaffoldError::DayExists(_))
+        ));
+    }
+}
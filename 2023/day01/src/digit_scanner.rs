@@ -0,0 +1,307 @@
+//! A single-pass scanner for digits that may also be spelled out as words,
+//! based on the Aho-Corasick algorithm.
+
+use anyhow::{bail, ensure};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, VecDeque},
+};
+
+/// The index of a node in the automaton. The root is at index 0.
+type NodeIndex = usize;
+
+const ROOT: NodeIndex = 0;
+
+/// A node of the trie of all words, extended by the links of the automaton.
+#[derive(Debug, Default)]
+struct Node {
+    children: BTreeMap<u8, NodeIndex>,
+    /// The node of the longest proper suffix of this node's word that is also
+    /// a prefix of some word, which is where matching continues if there is
+    /// no matching child.
+    fail: NodeIndex,
+    /// The length and digit of the word that ends at this node, if any.
+    word: Option<(usize, u32)>,
+    /// The next node on the chain of `fail` links whose word ends here, too.
+    output: Option<NodeIndex>,
+}
+
+/// Finds all occurrences of a table of words, each standing for a digit, in a
+/// single pass over a string. Occurrences may overlap, e.g. both `one` and
+/// `eight` occur in `oneight`.
+#[derive(Debug)]
+pub struct DigitScanner {
+    nodes: Vec<Node>,
+}
+
+/// An occurrence of a word in a scanned string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigitMatch {
+    /// The byte range of the word in the scanned string.
+    pub start: usize,
+    pub end: usize,
+    /// The digit the word stands for.
+    pub digit: u32,
+}
+
+impl DigitMatch {
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+impl DigitScanner {
+    /// Build the scanner for a table of `words` and the digits they stand
+    /// for. Words must not be empty, digits must lie between 0 and 9, and a
+    /// word may not stand for two different digits.
+    pub fn new<W, I>(words: I) -> anyhow::Result<Self>
+    where
+        W: AsRef<str>,
+        I: IntoIterator<Item = (W, u32)>,
+    {
+        let mut nodes = vec![Node::default()];
+
+        // Build the trie of all words.
+        for (word, digit) in words {
+            let word = word.as_ref();
+            ensure!(!word.is_empty(), "the word for {} is empty", digit);
+            ensure!(
+                digit <= 9,
+                "{:?} stands for {}, which is no digit",
+                word,
+                digit
+            );
+
+            let mut node = ROOT;
+            for &byte in word.as_bytes() {
+                node = match nodes[node].children.get(&byte) {
+                    Some(&child) => child,
+                    None => {
+                        nodes.push(Node::default());
+                        let child = nodes.len() - 1;
+                        nodes[node].children.insert(byte, child);
+                        child
+                    }
+                };
+            }
+
+            match nodes[node].word {
+                Some((_, other_digit)) if other_digit != digit => {
+                    bail!("{:?} stands for both {} and {}", word, other_digit, digit)
+                }
+                _ => nodes[node].word = Some((word.len(), digit)),
+            }
+        }
+
+        // Link each node to its longest proper suffix in the trie, in order of
+        // increasing depth, so that the links of all shorter suffixes exist.
+        let mut queue: VecDeque<NodeIndex> = nodes[ROOT].children.values().copied().collect();
+        while let Some(node) = queue.pop_front() {
+            let children: Vec<(u8, NodeIndex)> =
+                nodes[node].children.iter().map(|(&b, &c)| (b, c)).collect();
+
+            for (byte, child) in children {
+                let fail = match node {
+                    ROOT => ROOT,
+                    _ => Self::step(&nodes, nodes[node].fail, byte),
+                };
+                nodes[child].fail = fail;
+                nodes[child].output = match nodes[fail].word {
+                    Some(_) => Some(fail),
+                    None => nodes[fail].output,
+                };
+                queue.push_back(child);
+            }
+        }
+
+        Ok(Self { nodes })
+    }
+
+    /// The scanner for the ASCII digits `0` to `9` and the given `words`.
+    pub fn with_ascii_digits<W, I>(words: I) -> anyhow::Result<Self>
+    where
+        W: AsRef<str>,
+        I: IntoIterator<Item = (W, u32)>,
+    {
+        let ascii_digits = (0..10).map(|digit| (digit.to_string(), digit));
+        let words = words
+            .into_iter()
+            .map(|(word, digit)| (word.as_ref().to_string(), digit));
+
+        Self::new(ascii_digits.chain(words))
+    }
+
+    /// Follow the transition for `byte` from `node`, falling back along the
+    /// `fail` links until a node has a matching child.
+    fn step(nodes: &[Node], mut node: NodeIndex, byte: u8) -> NodeIndex {
+        loop {
+            if let Some(&child) = nodes[node].children.get(&byte) {
+                return child;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = nodes[node].fail;
+        }
+    }
+
+    /// Iterate over all, possibly overlapping, occurrences of words in `s`,
+    /// ordered by where they end.
+    pub fn matches<'a>(&'a self, s: &'a str) -> impl Iterator<Item = DigitMatch> + 'a {
+        s.bytes()
+            .enumerate()
+            .scan(ROOT, move |node, (index, byte)| {
+                *node = Self::step(&self.nodes, *node, byte);
+                Some((index + 1, *node))
+            })
+            .flat_map(move |(end, node)| {
+                // All words that end here, from the longest to the shortest.
+                let first = self.nodes[node]
+                    .word
+                    .map(|_| node)
+                    .or(self.nodes[node].output);
+                std::iter::successors(first, move |&node| self.nodes[node].output).map(
+                    move |node| {
+                        let (len, digit) = self.nodes[node].word.expect("output nodes end words");
+                        DigitMatch {
+                            start: end - len,
+                            end,
+                            digit,
+                        }
+                    },
+                )
+            })
+    }
+
+    /// Find the first and the last digit in `s`, in a single pass. The first
+    /// digit is the word that starts first, and the last digit the one that
+    /// ends last, where the longer word wins a tie.
+    pub fn first_and_last_digit(&self, s: &str) -> Option<(u32, u32)> {
+        let starts_first = |m: &DigitMatch| (m.start, Reverse(m.len()));
+
+        let (first, last) = self.matches(s).fold(None, |first_and_last, found| {
+            let Some((first, last)) = first_and_last else {
+                return Some((found, found));
+            };
+
+            let first = match starts_first(&found) < starts_first(&first) {
+                true => found,
+                false => first,
+            };
+            // Matches arrive ordered by where they end, and the longest first
+            // among those that end at the same place.
+            let last = match found.end > last.end {
+                true => found,
+                false => last,
+            };
+            Some((first, last))
+        })?;
+
+        Some((first.digit, last.digit))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Count how often each digit occurs in `s`, including overlapping words.
+    fn digit_counts(scanner: &DigitScanner, s: &str) -> HashMap<u32, usize> {
+        let mut counts = HashMap::new();
+        for found in scanner.matches(s) {
+            *counts.entry(found.digit).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    const ENGLISH: [(&str, u32); 9] = [
+        ("one", 1),
+        ("two", 2),
+        ("three", 3),
+        ("four", 4),
+        ("five", 5),
+        ("six", 6),
+        ("seven", 7),
+        ("eight", 8),
+        ("nine", 9),
+    ];
+
+    #[test]
+    fn test_overlapping_matches() -> anyhow::Result<()> {
+        let scanner = DigitScanner::with_ascii_digits(ENGLISH)?;
+
+        let digits: Vec<u32> = scanner.matches("oneight7twone").map(|m| m.digit).collect();
+        assert_eq!(vec![1, 8, 7, 2, 1], digits);
+
+        assert_eq!(Some((1, 8)), scanner.first_and_last_digit("oneight"));
+        assert_eq!(
+            Some((2, 1)),
+            scanner.first_and_last_digit("xtwone3four1twone")
+        );
+        assert_eq!(Some((7, 7)), scanner.first_and_last_digit("abc7xyz"));
+        assert_eq!(None, scanner.first_and_last_digit("abcxyz"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_words() -> anyhow::Result<()> {
+        // `sieben` and `siebzehn` share the prefix `sieb`, and `zehn` ends
+        // `siebzehn`.
+        let scanner = DigitScanner::new([("sieben", 7), ("siebzehn", 1), ("zehn", 0)])?;
+
+        let matches: Vec<DigitMatch> = scanner.matches("siebzehnsieben").collect();
+        assert_eq!(
+            vec![
+                DigitMatch {
+                    start: 0,
+                    end: 8,
+                    digit: 1
+                },
+                DigitMatch {
+                    start: 4,
+                    end: 8,
+                    digit: 0
+                },
+                DigitMatch {
+                    start: 8,
+                    end: 14,
+                    digit: 7
+                },
+            ],
+            matches
+        );
+
+        // `zehn` ends just like `siebzehn`, which is longer and wins.
+        assert_eq!(Some((1, 1)), scanner.first_and_last_digit("siebzehn"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_configurable_word_table() -> anyhow::Result<()> {
+        let german = [
+            ("eins", 1),
+            ("zwei", 2),
+            ("drei", 3),
+            ("acht", 8),
+            ("neun", 9),
+        ];
+        let scanner = DigitScanner::with_ascii_digits(german)?;
+
+        assert_eq!(Some((2, 8)), scanner.first_and_last_digit("zweiundachtzig"));
+        assert_eq!(Some((3, 9)), scanner.first_and_last_digit("dreineun"));
+        assert_eq!(
+            HashMap::from([(1, 2), (5, 1)]),
+            digit_counts(&scanner, "eins5eins")
+        );
+
+        assert!(DigitScanner::new([("", 1)]).is_err());
+        assert!(DigitScanner::new([("zehn", 10)]).is_err());
+        assert!(DigitScanner::new([("eins", 1), ("eins", 2)]).is_err());
+        assert!(DigitScanner::new([("eins", 1), ("eins", 1)]).is_ok());
+
+        Ok(())
+    }
+}
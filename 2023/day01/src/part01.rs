@@ -1,10 +1,12 @@
+use anyhow::Context;
+use aoc_solution::Solution;
 use derive_more::Into;
 use std::str::FromStr;
 
 /// The calibration value can be found by combining the first digit and the
 /// last digit (in that order) to form a single two-digit number.
 #[derive(Into)]
-struct CalibrationValue(u32);
+pub struct CalibrationValue(u32);
 
 impl FromStr for CalibrationValue {
     type Err = anyhow::Error;
@@ -12,24 +14,32 @@ impl FromStr for CalibrationValue {
         let first_digit = s
             .chars()
             .find_map(|c| c.to_digit(10))
-            .expect("there should always be a first digit");
+            .context("expected at least one digit")?;
 
         let last_digit = s
             .chars()
             .rev()
             .find_map(|c| c.to_digit(10))
-            .expect("there should always be a last digit");
+            .context("expected at least one digit")?;
 
         Ok(Self(first_digit * 10 + last_digit))
     }
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let calibration_values_sum: u32 = puzzle_input
-        .lines()
-        .map(|line| line.parse::<CalibrationValue>().unwrap())
-        .map(u32::from)
-        .sum();
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<CalibrationValue>> {
+    let calibration_values = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(calibration_values)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(calibration_values: Vec<CalibrationValue>) -> anyhow::Result<Solution> {
+    let calibration_values_sum: u32 = calibration_values.into_iter().map(u32::from).sum();
+
+    Ok(calibration_values_sum.into())
+}
 
-    Ok(calibration_values_sum.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
@@ -1,40 +1,13 @@
+use crate::digit_scanner::DigitScanner;
+use anyhow::Context;
+use aoc_solution::Solution;
 use derive_more::Into;
-use parse_display::FromStr;
-use std::str::FromStr;
-
-/// A digit that can be parsed from both a `SpelledOutDigit` and a `char`.
-#[derive(Into)]
-struct Digit(u32);
-
-impl FromStr for Digit {
-    type Err = anyhow::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Try parsing the string into a `SpelledOutDigit` first.
-        let digit: u32 = match s.parse::<SpelledOutDigit>() {
-            Ok(digit) => digit.into(),
-            // Try parsing a char into a u32.
-            Err(_) => match s.parse::<char>() {
-                Ok(c) => match c.to_digit(10) {
-                    Some(digit) => digit,
-                    None => anyhow::bail!("Failed to convert char to digit: {}", c),
-                },
-                Err(_) => anyhow::bail!("Failed to convert string to char: {}", s),
-            },
-        };
-        Ok(Self(digit))
-    }
-}
-
-#[test]
-fn test_parsing_digit() {
-    assert_eq!(2u32, "two".parse::<Digit>().expect("should parse").into());
-    assert_eq!(2u32, "2".parse::<Digit>().expect("should parse").into());
-
-    assert!("c".parse::<Digit>().is_err());
-}
+use parse_display::{Display, FromStr};
+use std::{str::FromStr, sync::OnceLock};
+use strum::{EnumIter, IntoEnumIterator};
 
 /// Spelled out digits without `Zero`.
-#[derive(FromStr)]
+#[derive(Display, FromStr, Debug, Clone, Copy, EnumIter)]
 #[display(style = "lowercase")]
 enum SpelledOutDigit {
     One = 1,
@@ -63,65 +36,62 @@ fn test_parsing_spelled_out_digit() {
     assert!("not a number".parse::<SpelledOutDigit>().is_err());
 }
 
-/// Find the first `Digit` in a string, if one exists.
-fn find_first_digit(s: &str) -> Option<Digit> {
-    // Use a sliding window.
-    for window_start in 0..s.len() {
-        for window_end in window_start..s.len() {
-            if let Ok(digit) = s[window_start..=window_end].parse::<Digit>() {
-                return Some(digit);
-            }
-        }
-    }
-    None
-}
-
-/// Find the last `Digit` in a string, if one exists.
-fn find_last_digit(s: &str) -> Option<Digit> {
-    // Use a sliding window.
-    for window_end in (0..s.len()).rev() {
-        for window_start in (0..=window_end).rev() {
-            if let Ok(digit) = s[window_start..=window_end].parse::<Digit>() {
-                return Some(digit);
-            }
-        }
-    }
-    None
-}
-
-#[test]
-fn test_find_first_digit() {
-    assert_eq!(2u32, find_first_digit("abc23").unwrap().into());
-    assert_eq!(4u32, find_first_digit("abcfour3").unwrap().into());
-}
-
-#[test]
-fn test_find_last_digit() {
-    assert_eq!(9u32, find_last_digit("4abc9").unwrap().into());
-    assert_eq!(1u32, find_last_digit("threeabc2oneabc").unwrap().into());
+/// The scanner for both ASCII digits and `SpelledOutDigit`s, which is built
+/// once and shared by all lines.
+fn digit_scanner() -> &'static DigitScanner {
+    static DIGIT_SCANNER: OnceLock<DigitScanner> = OnceLock::new();
+    DIGIT_SCANNER.get_or_init(|| {
+        let words = SpelledOutDigit::iter().map(|digit| (digit.to_string(), u32::from(digit)));
+        DigitScanner::with_ascii_digits(words).expect("spelled out digits should be distinct")
+    })
 }
 
 /// The calibration value can be found by combining the first digit and the
 /// last digit (in that order) to form a single two-digit number.
 #[derive(Into)]
-struct CalibrationValue(u32);
+pub struct CalibrationValue(u32);
 
 impl FromStr for CalibrationValue {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let first_digit = find_first_digit(s).expect("there should always be a first digit");
-        let last_digit = find_last_digit(s).expect("there should always be a last digit");
+        let (first_digit, last_digit) = digit_scanner()
+            .first_and_last_digit(s)
+            .context("expected at least one digit")?;
 
-        Ok(Self(u32::from(first_digit) * 10 + u32::from(last_digit)))
+        Ok(Self(first_digit * 10 + last_digit))
     }
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let calibration_values_sum: u32 = puzzle_input
-        .lines()
-        .map(|line| line.parse::<CalibrationValue>().unwrap())
-        .map(u32::from)
-        .sum();
+#[test]
+fn test_parsing_calibration_value() {
+    let calibration_value =
+        |s: &str| u32::from(s.parse::<CalibrationValue>().expect("should parse"));
+
+    assert_eq!(23, calibration_value("abc23"));
+    assert_eq!(43, calibration_value("abcfour3"));
+    assert_eq!(49, calibration_value("4abc9"));
+    assert_eq!(31, calibration_value("threeabc2oneabc"));
+    // Overlapping spelled out digits both count.
+    assert_eq!(18, calibration_value("oneight"));
+    assert_eq!(77, calibration_value("7"));
+
+    assert!("abc".parse::<CalibrationValue>().is_err());
+}
+
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<CalibrationValue>> {
+    let calibration_values = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(calibration_values)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(calibration_values: Vec<CalibrationValue>) -> anyhow::Result<Solution> {
+    let calibration_values_sum: u32 = calibration_values.into_iter().map(u32::from).sum();
+
+    Ok(calibration_values_sum.into())
+}
 
-    Ok(calibration_values_sum.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
@@ -1,4 +1,5 @@
 use anyhow::{Error, Result};
+use aoc_solution::Solution;
 use derive_more::From;
 use derive_new::new;
 use parse_display::{FromStr, ParseError};
@@ -13,7 +14,7 @@ const BLUE_CUBES_IN_BAG: usize = 14;
 #[derive(FromStr, new)]
 #[display("Game {id}: {cube_subsets}")]
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
-struct Game {
+pub struct Game {
     id: usize,
     cube_subsets: CubePickingSubsets,
 }
@@ -129,11 +130,22 @@ enum CubeColor {
     Blue,
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let ids_sum: usize = puzzle_input
-        .lines()
-        .map(|line| line.parse::<Game>().unwrap())
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<Game>> {
+    let games = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(games)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(games: Vec<Game>) -> anyhow::Result<Solution> {
+    let ids_sum: usize = games
+        .into_iter()
         .filter_map(|game| game.is_possible().then_some(game.id))
         .sum();
-    Ok(ids_sum.to_string())
+    Ok(ids_sum.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
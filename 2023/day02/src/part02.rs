@@ -1,4 +1,5 @@
 use anyhow::{Error, Result};
+use aoc_solution::Solution;
 use derive_more::From;
 use derive_new::new;
 use parse_display::{FromStr, ParseError};
@@ -9,7 +10,7 @@ use std::str;
 #[derive(FromStr, new)]
 #[display("Game {_id}: {cube_subsets}")]
 #[cfg_attr(test, derive(PartialEq, Eq, Debug))]
-struct Game {
+pub struct Game {
     _id: usize,
     cube_subsets: CubePickingSubsets,
 }
@@ -150,12 +151,23 @@ enum CubeColor {
     Blue,
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let set_power_sum: usize = puzzle_input
-        .lines()
-        .map(|line| line.parse::<Game>().unwrap())
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<Game>> {
+    let games = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(games)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(games: Vec<Game>) -> anyhow::Result<Solution> {
+    let set_power_sum: usize = games
+        .into_iter()
         .map(|game| game.find_minimum_possible_bag())
         .map(|bag| bag.power())
         .sum();
-    Ok(set_power_sum.to_string())
+    Ok(set_power_sum.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
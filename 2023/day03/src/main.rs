@@ -1,8 +1,7 @@
-mod part01;
-mod part02;
+use day03::{part01, part02, BUNDLED_PUZZLE_INPUT};
 
 fn main() -> anyhow::Result<()> {
-    let puzzle_input = include_str!("../puzzle_input.txt");
+    let puzzle_input = aoc_input::from_args(BUNDLED_PUZZLE_INPUT)?;
 
     println!("Part 01: {}", part01::solve(&puzzle_input)?);
     println!("Part 02: {}", part02::solve(&puzzle_input)?);
@@ -1,3 +1,4 @@
+use aoc_solution::Solution;
 use derive_new::new;
 use itertools::Itertools;
 use std::{ops::RangeInclusive, str};
@@ -5,7 +6,7 @@ use strum::EnumIs;
 
 /// A 2D grid of `Char`s.
 #[cfg_attr(test, derive(Eq, PartialEq, Debug))]
-struct CharGrid(Vec<Vec<Char>>);
+pub struct CharGrid(Vec<Vec<Char>>);
 
 impl str::FromStr for CharGrid {
     type Err = anyhow::Error;
@@ -63,7 +64,7 @@ impl CharGrid {
             self.0
                 .get(row_idx)
                 .and_then(|row| row.get(col_idx))
-                .map_or(false, Char::is_symbol)
+                .is_some_and(Char::is_symbol)
         })
     }
 }
@@ -238,8 +239,13 @@ impl From<char> for Char {
     }
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let char_grid: CharGrid = puzzle_input.parse()?;
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<CharGrid> {
+    puzzle_input.parse()
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(char_grid: CharGrid) -> anyhow::Result<Solution> {
     // TODO: since we're calling iter() here anyway, find_numbers() should just return an iterator
     let part_number_sum: usize = char_grid
         .find_numbers()
@@ -248,5 +254,10 @@ pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
         .map(usize::from)
         .sum();
 
-    Ok(part_number_sum.to_string())
+    Ok(part_number_sum.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
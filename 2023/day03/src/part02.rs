@@ -1,3 +1,5 @@
+use anyhow::ensure;
+use aoc_solution::Solution;
 use derive_new::new;
 use itertools::Itertools;
 use std::{collections::HashMap, ops::RangeInclusive, str};
@@ -7,7 +9,7 @@ use uuid::Uuid;
 /// A 2D grid of `Char`s.
 #[derive(Clone)]
 #[cfg_attr(test, derive(Eq, PartialEq, Debug))]
-struct CharGrid(Vec<Vec<Char>>);
+pub struct CharGrid(Vec<Vec<Char>>);
 
 impl str::FromStr for CharGrid {
     type Err = anyhow::Error;
@@ -66,9 +68,7 @@ impl CharGrid {
         row_idx: usize,
         col_range: RangeInclusive<usize>,
     ) -> Option<Vec<Gear>> {
-        let Some(row) = self.0.get(row_idx) else {
-            return None;
-        };
+        let row = self.0.get(row_idx)?;
         let gears: Vec<Gear> = col_range
             .into_iter()
             .filter_map(|col_idx| match row.get(col_idx) {
@@ -174,28 +174,6 @@ impl Number {
     }
 }
 
-// TODO: turn this into an actual rust crate. Potentially name `LazyBoolOr`.
-trait LazyBool {
-    /// If `self` is `true`, return `true`, otherwise evaluate `f` and return
-    /// its returned value.
-    fn or_else<F>(self, f: F) -> bool
-    where
-        F: FnOnce() -> bool;
-}
-
-impl LazyBool for bool {
-    fn or_else<F>(self, f: F) -> bool
-    where
-        F: FnOnce() -> bool,
-    {
-        if self {
-            true
-        } else {
-            f()
-        }
-    }
-}
-
 impl FromIterator<CharWithPosition> for Number {
     fn from_iter<T: IntoIterator<Item = CharWithPosition>>(chars_iter: T) -> Self {
         let mut iter = chars_iter.into_iter().peekable();
@@ -336,10 +314,10 @@ fn solution_hashmap(char_grid: CharGrid) -> usize {
         });
 
     let gear_ratio_sum: usize = gear_number_map
-        .into_iter()
-        .filter_map(|(_, numbers)| {
+        .into_values()
+        .filter_map(|numbers| {
             // A gear must be adjacent to **exactly** two part numbers.
-            match (numbers.get(0), numbers.get(1), numbers.get(2)) {
+            match (numbers.first(), numbers.get(1), numbers.get(2)) {
                 (Some(first), Some(second), None) => Some(first.gear_ratio(second)),
                 _ => None,
             }
@@ -349,14 +327,28 @@ fn solution_hashmap(char_grid: CharGrid) -> usize {
     gear_ratio_sum
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let char_grid: CharGrid = puzzle_input.parse()?;
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<CharGrid> {
+    puzzle_input.parse()
+}
 
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(char_grid: CharGrid) -> anyhow::Result<Solution> {
     let grouped_by = solution_grouped_by(char_grid.clone());
     let hashmap = solution_hashmap(char_grid);
 
-    Ok(format!(
-        "grouped_by solution: {}, hashmap solution: {}",
-        grouped_by, hashmap
-    ))
+    // Both approaches must agree.
+    ensure!(
+        grouped_by == hashmap,
+        "the grouped_by solution {} differs from the hashmap solution {}",
+        grouped_by,
+        hashmap
+    );
+
+    Ok(grouped_by.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
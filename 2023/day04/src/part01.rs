@@ -1,20 +1,21 @@
 use anyhow::Result;
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_more::From;
 use itertools::{EitherOrBoth, Itertools};
 use nom::{
     bytes::complete::tag,
     character::complete::{char, digit1, multispace0, multispace1},
     combinator::map_res,
-    error::Error as NomError,
     multi::separated_list0,
     sequence::preceded,
-    Finish, IResult,
+    IResult,
 };
 use std::str;
 
 #[derive(Debug)]
 #[cfg_attr(test, derive(Eq, PartialEq))]
-struct ScratchCard {
+pub struct ScratchCard {
     _id: usize,
     winning_numbers: Numbers,
     picked_numbers: Numbers,
@@ -42,17 +43,10 @@ impl ScratchCard {
     }
 }
 
-// TODO: this is boilerplate code that should be covered by nom
 impl str::FromStr for ScratchCard {
-    type Err = NomError<String>;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match Self::parse(s).finish() {
-            Ok((_remaining, scatchcard)) => Ok(scatchcard),
-            Err(NomError { input, code }) => Err(NomError {
-                input: input.to_string(),
-                code,
-            }),
-        }
+        aoc_parse::parse_all(s, Self::parse)
     }
 }
 
@@ -100,7 +94,7 @@ impl ScratchCard {
         let winning_numbers_iter = self.winning_numbers.0.iter().sorted();
         let picked_numbers_iter = self.picked_numbers.0.iter().sorted();
         winning_numbers_iter
-            .merge_join_by(picked_numbers_iter, |a, b| Ord::cmp(a, b))
+            .merge_join_by(picked_numbers_iter, Ord::cmp)
             .filter_map(|either| match either {
                 EitherOrBoth::Both(a, _) => Some(a),
                 _ => None,
@@ -151,12 +145,23 @@ fn test_parsing_numbers() {
     );
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let points_worth: usize = puzzle_input
-        .lines()
-        .map(|line| line.parse::<ScratchCard>().unwrap())
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<ScratchCard>> {
+    let scratch_cards = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(scratch_cards)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(scratch_cards: Vec<ScratchCard>) -> anyhow::Result<Solution> {
+    let points_worth: usize = scratch_cards
+        .into_iter()
         .map(|scratch_card| scratch_card.points_worth())
         .sum();
 
-    Ok(points_worth.to_string())
+    Ok(points_worth.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
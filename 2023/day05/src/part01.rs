@@ -1,9 +1,12 @@
+use anyhow::Context;
+use aoc_solution::Solution;
 use derive_more::From;
 use derive_new::new;
 use nom::{
+    branch::alt,
     bytes::complete::tag,
     character::complete::{digit1, line_ending, newline, not_line_ending, space1},
-    combinator::map_res,
+    combinator::{eof, map_res},
     multi::{many1, separated_list0},
     sequence::terminated,
     IResult,
@@ -12,11 +15,11 @@ use parse_display::FromStr;
 use std::ops::Range;
 
 #[derive(Debug, From)]
-struct Seeds(Vec<Seed>);
+pub struct Seeds(Vec<Seed>);
 
 impl Seeds {
     /// Parse from:
-    /// ```
+    /// ```text
     /// seeds: 79 14 55 13
     /// ```
     fn parse(input: &str) -> IResult<&str, Seeds> {
@@ -59,7 +62,7 @@ impl Seed {
 /// maps only differ in their position in the underlying vector (0th and 1st,
 /// respectively).
 #[derive(Debug, From)]
-struct AllMaps(Vec<Map>);
+pub struct AllMaps(Vec<Map>);
 
 type Src = QuantityId;
 type Dst = QuantityId;
@@ -86,7 +89,7 @@ impl Map {
     }
 
     /// Parse from:
-    /// ```
+    /// ```text
     /// {map_name} map:
     /// 50 98 2
     /// 52 50 48
@@ -97,7 +100,8 @@ impl Map {
 
         let (input, _) = tag(format!("{} map:", map_name).as_str())(input)?;
         let (input, _) = newline(input)?;
-        let (input, parsed_maps) = many1(terminated(parsed_map_parser, line_ending))(input)?;
+        let (input, parsed_maps) =
+            many1(terminated(parsed_map_parser, alt((line_ending, eof))))(input)?;
 
         Ok((input, Map::from(parsed_maps)))
     }
@@ -152,7 +156,7 @@ struct MapParsed {
 }
 
 /// Parse `Seeds` and `AllMaps` from:
-/// ```
+/// ```text
 /// seeds: 79 14 55 13
 ///
 /// seed-to-soil map:
@@ -184,7 +188,7 @@ struct MapParsed {
 /// 60 56 37
 /// ...
 /// ```
-fn parse(input: &str) -> IResult<&str, (Seeds, AllMaps)> {
+fn parse_almanac(input: &str) -> IResult<&str, (Seeds, AllMaps)> {
     let (input, seeds) = Seeds::parse(input)?;
     let (input, _) = newline(input)?;
 
@@ -215,27 +219,35 @@ fn parse(input: &str) -> IResult<&str, (Seeds, AllMaps)> {
     Ok((input, (seeds, all_maps)))
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    // TODO: remove unwrap (replacing with ? leads to a borrow-checking issue related to nom's result type)
-    let (_leftover, (seeds, all_maps)) = parse(puzzle_input).unwrap();
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<(Seeds, AllMaps)> {
+    Ok(aoc_parse::parse_all(puzzle_input, parse_almanac)?)
+}
 
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed((seeds, all_maps): (Seeds, AllMaps)) -> anyhow::Result<Solution> {
     let lowest_location_number: usize = seeds
         .0
         .into_iter()
         .map(|seed| seed.find_corresponding_location(&all_maps))
         .min()
-        .expect("there should be at least one seed");
+        .context("there should be at least one seed")?;
+
+    Ok(lowest_location_number.into())
+}
 
-    Ok(lowest_location_number.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details<'a>() -> (&'a str, String) {
+    pub fn example_details<'a>() -> (&'a str, Solution) {
         let puzzle_input = indoc! {"
             seeds: 79 14 55 13
 
@@ -272,6 +284,6 @@ pub mod example {
             56 93 4
         "};
         let expected_solution = 35;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
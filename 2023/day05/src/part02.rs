@@ -1,24 +1,25 @@
+use anyhow::Context;
+use aoc_solution::Solution;
 use derive_more::From;
 use derive_new::new;
-use indicatif::ParallelProgressIterator;
 use nom::{
+    branch::alt,
     bytes::complete::tag,
     character::complete::{char, digit1, line_ending, newline, not_line_ending},
-    combinator::{map, map_res},
+    combinator::{eof, map, map_res},
     multi::{many1, separated_list0},
     sequence::{separated_pair, terminated},
     IResult,
 };
 use parse_display::FromStr;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use std::ops::Range;
 
 #[derive(Debug, From)]
-struct SeedRanges(Vec<SeedRange>);
+pub struct SeedRanges(Vec<SeedRange>);
 
 impl SeedRanges {
     /// Parse from:
-    /// ```
+    /// ```text
     /// seeds: [{range_start} {range_len}]*
     /// ```
     fn parse(input: &str) -> IResult<&str, SeedRanges> {
@@ -49,22 +50,23 @@ struct SeedRange(Range<SeedType>);
 
 type SeedType = QuantityId;
 
-/// A seed, identified by an `Id`.
-#[derive(Debug, FromStr, From, new)]
-struct Seed(SeedType);
-
-impl Seed {
-    /// Find the location number corresponding to this seed by traversing all
-    /// maps until the end (the final map maps to locations).
-    fn find_corresponding_location(&self, all_maps: &AllMaps) -> QuantityId {
-        let initial_seed = self.0;
-        let number = all_maps
+impl SeedRange {
+    /// Find the location ranges corresponding to this seed range by
+    /// traversing all maps until the end (the final map maps to locations).
+    /// Instead of mapping each seed individually, whole ranges are mapped and
+    /// split wherever they cross the boundaries of a `MapRange`.
+    fn find_corresponding_location_ranges(&self, all_maps: &AllMaps) -> Vec<Range<QuantityId>> {
+        let initial_ranges = vec![self.0.clone()];
+        all_maps
             .0
             .iter()
-            // Use previous `value` as key to find next value in map.
-            .fold(initial_seed, |value, map| map.get(&value));
-
-        number
+            // Use previous `ranges` as keys to find next ranges in map.
+            .fold(initial_ranges, |ranges, map| {
+                ranges
+                    .into_iter()
+                    .flat_map(|range| map.get_ranges(range))
+                    .collect()
+            })
     }
 }
 
@@ -74,33 +76,49 @@ impl Seed {
 /// maps only differ in their position in the underlying vector (0th and 1st,
 /// respectively).
 #[derive(Debug, From)]
-struct AllMaps(Vec<Map>);
+pub struct AllMaps(Vec<Map>);
 
 type Src = QuantityId;
 type Dst = QuantityId;
 
 /// A range-based hashmap-like structure that maps a `Src` quantity to a `Dst`
-/// quantity, and provides `get(Src) -> Dst` for all any possible `Src` key.
+/// quantity, and provides `get_ranges(Range<Src>) -> [Range<Dst>]` for all
+/// any possible `Src` key range.
 /// Very large range lengths in the puzzle input make it infeasible to use
 /// a regular `HashMap`.
 #[derive(Debug)]
 struct Map(Vec<MapRange>);
 
 impl Map {
-    /// Get the `Dst` value that belongs to the `Src` key. This will always
-    /// succeed, because if the `Src->Dst` mapping is not stored in this `Map`
-    /// directly, the `Src` will be returned.
-    fn get(&self, src: &Src) -> Dst {
-        self.0
-            .iter()
-            // Check if `Src` is in any of our mappings.
-            .find_map(|map_range| map_range.get(src))
-            // Else return `Src`.
-            .unwrap_or(*src)
+    /// Get the `Dst` ranges that belong to the `Src` key range. Every `Src`
+    /// key will be mapped, because if the `Src->Dst` mapping is not stored in
+    /// this `Map` directly, the `Src` will be returned. The `Src` range is
+    /// split into pieces at the boundaries of the `MapRange`s it overlaps with.
+    fn get_ranges(&self, src_range: Range<Src>) -> Vec<Range<Dst>> {
+        let mut dst_ranges = Vec::new();
+
+        // Pieces of the `Src` range that have not been mapped by any of our
+        // mappings yet.
+        let mut unmapped = vec![src_range];
+        for map_range in self.0.iter() {
+            unmapped = unmapped
+                .into_iter()
+                .flat_map(|src_range| {
+                    let (dst_range, leftovers) = map_range.get_range(&src_range);
+                    dst_ranges.extend(dst_range);
+                    leftovers
+                })
+                .collect();
+        }
+
+        // Any `Src` that is not in any of our mappings maps to itself.
+        dst_ranges.extend(unmapped);
+
+        dst_ranges
     }
 
     /// Parse from:
-    /// ```
+    /// ```text
     /// {map_name} map:
     /// 50 98 2
     /// 52 50 48
@@ -111,7 +129,8 @@ impl Map {
 
         let (input, _) = tag(format!("{} map:", map_name).as_str())(input)?;
         let (input, _) = newline(input)?;
-        let (input, parsed_maps) = many1(terminated(parsed_map_parser, line_ending))(input)?;
+        let (input, parsed_maps) =
+            many1(terminated(parsed_map_parser, alt((line_ending, eof))))(input)?;
 
         Ok((input, Map::from(parsed_maps)))
     }
@@ -139,6 +158,31 @@ where
     }
 }
 
+#[test]
+fn test_map_get_ranges() {
+    let map = Map::from([
+        MapParsed {
+            dst_range_start: 50,
+            src_range_start: 98,
+            range_len: 2,
+        },
+        MapParsed {
+            dst_range_start: 52,
+            src_range_start: 50,
+            range_len: 48,
+        },
+    ]);
+
+    // Entirely unmapped.
+    assert_eq!(map.get_ranges(0..10), vec![0..10]);
+    // Entirely inside one `MapRange`.
+    assert_eq!(map.get_ranges(79..93), vec![81..95]);
+    // Split across both `MapRange`s and the unmapped keys on either side.
+    let mut dst_ranges = map.get_ranges(40..105);
+    dst_ranges.sort_by_key(|range| range.start);
+    assert_eq!(dst_ranges, vec![40..50, 50..52, 52..100, 100..105]);
+}
+
 /// Map a range of `Src` values to a range of `Dst` values. Both ranges have
 /// equal lengths.
 #[derive(Debug, new)]
@@ -150,12 +194,27 @@ struct MapRange {
 }
 
 impl MapRange {
-    /// Get the `Dst` value that belongs to the `Src` key, if it exists.
-    fn get(&self, src: &Src) -> Option<Dst> {
-        self.src_range.contains(src).then(|| {
-            let idx_into_src_range = src - self.src_range.start;
-            self.dst_first + idx_into_src_range
-        })
+    /// Get the `Dst` range that belongs to the part of the `Src` key range
+    /// that overlaps with our `Src` range, if it exists. Also return the parts
+    /// of the `Src` key range (at most two, below and above our `Src` range)
+    /// that we do not map.
+    fn get_range(&self, src: &Range<Src>) -> (Option<Range<Dst>>, Vec<Range<Src>>) {
+        let overlap_start = src.start.max(self.src_range.start);
+        let overlap_end = src.end.min(self.src_range.end);
+
+        if overlap_start >= overlap_end {
+            return (None, vec![src.clone()]);
+        }
+
+        let offset = |src| self.dst_first + (src - self.src_range.start);
+        let dst_range = offset(overlap_start)..offset(overlap_end);
+
+        let leftovers = [src.start..overlap_start, overlap_end..src.end]
+            .into_iter()
+            .filter(|range| !range.is_empty())
+            .collect();
+
+        (Some(dst_range), leftovers)
     }
 }
 
@@ -168,7 +227,7 @@ struct MapParsed {
 }
 
 /// Parse `Seeds` and `AllMaps` from:
-/// ```
+/// ```text
 /// seeds: 79 14 55 13
 ///
 /// seed-to-soil map:
@@ -200,7 +259,7 @@ struct MapParsed {
 /// 60 56 37
 /// ...
 /// ```
-fn parse(input: &str) -> IResult<&str, (SeedRanges, AllMaps)> {
+fn parse_almanac(input: &str) -> IResult<&str, (SeedRanges, AllMaps)> {
     let (input, seed_ranges) = SeedRanges::parse(input)?;
     let (input, _) = newline(input)?;
 
@@ -231,36 +290,36 @@ fn parse(input: &str) -> IResult<&str, (SeedRanges, AllMaps)> {
     Ok((input, (seed_ranges, all_maps)))
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    // TODO: remove unwrap (replacing with ? leads to a borrow-checking issue related to nom's result type)
-    let (_leftover, (seed_ranges, all_maps)) = parse(puzzle_input).unwrap();
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<(SeedRanges, AllMaps)> {
+    Ok(aoc_parse::parse_all(puzzle_input, parse_almanac)?)
+}
 
-    // We have to collect to get an `ExactSizeIterator` for the progress bar to work.
-    let seeds: Vec<Seed> = seed_ranges
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed((seed_ranges, all_maps): (SeedRanges, AllMaps)) -> anyhow::Result<Solution> {
+    let lowest_location_number: usize = seed_ranges
         .0
-        .into_par_iter()
-        .flat_map(|seed_range| seed_range.0)
-        .map(Seed::new)
-        .collect();
-
-    let lowest_location_number: usize = seeds
-        // Rayon magic parallelization.
-        .into_par_iter()
-        .progress()
-        .map(|seed| seed.find_corresponding_location(&all_maps))
+        .iter()
+        .flat_map(|seed_range| seed_range.find_corresponding_location_ranges(&all_maps))
+        .map(|location_range| location_range.start)
         .min()
-        .expect("at least one seed should be able to reach the location");
+        .context("at least one seed should be able to reach the location")?;
+
+    Ok(lowest_location_number.into())
+}
 
-    Ok(lowest_location_number.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details<'a>() -> (&'a str, String) {
+    pub fn example_details<'a>() -> (&'a str, Solution) {
         let puzzle_input = indoc! {"
             seeds: 79 14 55 13
 
@@ -297,6 +356,6 @@ pub mod example {
             56 93 4
         "};
         let expected_solution = 46;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
@@ -0,0 +1,11 @@
+pub mod part01;
+pub mod part02;
+pub mod race;
+
+/// The path of the puzzle input that is bundled with this day. It is only
+/// read at runtime, so the day builds even without it.
+pub const BUNDLED_PUZZLE_INPUT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/puzzle_input.txt");
+
+/// The accepted answers for the bundled puzzle input, one `part <n>: <answer>`
+/// line per part whose answer has been recorded.
+pub const ANSWERS: &str = include_str!("../answers.txt");
@@ -0,0 +1,163 @@
+use derive_new::new;
+
+// TODO: it would be cool to use dedicated `Milliseconds`, `Millimeters` and `Millimeters/Milliseconds` types instead of u64, to ensure type safety
+
+#[derive(Debug, new)]
+#[cfg_attr(test, derive(Eq, PartialEq))]
+pub struct Race {
+    /// The total length in time of the race, in milliseconds.
+    total_time: u64,
+    /// The record distance in this race, in millimeters.
+    record_distance: u64,
+}
+
+impl Race {
+    /// Calculate the number of record beating race possibilities.
+    ///
+    /// Holding the button for `h` milliseconds covers `h * (T - h)`
+    /// millimeters, so we are looking for all whole `h` with
+    /// `h^2 - T*h + D < 0`, which lie strictly between the roots
+    /// `(T ± sqrt(T^2 - 4D)) / 2`. The square root is only computed as an
+    /// integer, and the first record beating holding time is then corrected to
+    /// the exact boundary, so there are no floating point rounding errors.
+    /// Since the distances are symmetric around `T / 2`, the last record
+    /// beating holding time mirrors the first one.
+    pub fn record_beating_possibilities(&self) -> u64 {
+        let total_time = u128::from(self.total_time);
+        let record_distance = u128::from(self.record_distance);
+
+        // The squares of `u64`s only fit into `u128`s.
+        let Some(discriminant) = (total_time * total_time).checked_sub(4 * record_distance) else {
+            return 0;
+        };
+
+        let beats_record =
+            |holding_time: u128| holding_time * (total_time - holding_time) > record_distance;
+
+        let mut first_holding_time = (total_time - discriminant.isqrt()) / 2;
+        while first_holding_time > 0 && beats_record(first_holding_time - 1) {
+            first_holding_time -= 1;
+        }
+        while first_holding_time <= total_time / 2 && !beats_record(first_holding_time) {
+            first_holding_time += 1;
+        }
+        if first_holding_time > total_time / 2 {
+            return 0;
+        }
+
+        let last_holding_time = total_time - first_holding_time;
+        let possibilities = last_holding_time - first_holding_time + 1;
+        u64::try_from(possibilities)
+            .expect("there are at most as many possibilities as milliseconds")
+    }
+
+    /// Calculate the number of record beating race possibilities by trying out
+    /// every holding time.
+    #[cfg(test)]
+    fn record_beating_possibilities_by_enumeration(&self) -> u64 {
+        (0..=self.total_time)
+            .map(|holding_time| PossibleRace::new(holding_time, self.total_time))
+            .filter(|possible_race| possible_race.beats_record(self.record_distance))
+            .count() as u64
+    }
+}
+
+/// There are different possibilities of how a race can go.
+/// For each whole millisecond you spend at the beginning of the race holding
+/// down the button (`holding_time`), the boat's speed increases by one
+/// millimeter per millisecond.
+#[cfg(test)]
+#[derive(new)]
+struct PossibleRace {
+    holding_time: u64,
+    total_race_time: u64,
+}
+
+#[cfg(test)]
+impl PossibleRace {
+    /// Check if this race possibility beats the record distance.
+    fn beats_record(&self, record_distance: u64) -> bool {
+        self.distance_covered() > record_distance
+    }
+
+    /// Calculate the total distance covered in a `total_time` amount of time.
+    fn distance_covered(&self) -> u64 {
+        let moving_speed = self.speed();
+        let moving_time = self.total_race_time - self.holding_time;
+        moving_time * moving_speed
+    }
+
+    /// Calculate the speed the boat will reach if it is held according to the
+    /// `holding_time`.
+    fn speed(&self) -> u64 {
+        self.holding_time
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_record_beating_possibilities() {
+        assert_eq!(4, Race::new(7, 9).record_beating_possibilities());
+        assert_eq!(8, Race::new(15, 40).record_beating_possibilities());
+        assert_eq!(9, Race::new(30, 200).record_beating_possibilities());
+        assert_eq!(
+            71503,
+            Race::new(71530, 940200).record_beating_possibilities()
+        );
+
+        // The record can only be matched, but not beaten, by holding for 5ms.
+        assert_eq!(0, Race::new(10, 25).record_beating_possibilities());
+        assert_eq!(1, Race::new(10, 24).record_beating_possibilities());
+        assert_eq!(0, Race::new(0, 0).record_beating_possibilities());
+        assert_eq!(0, Race::new(1, 0).record_beating_possibilities());
+    }
+
+    #[test]
+    fn test_record_beating_possibilities_beyond_u64_products() {
+        // Every holding time but the first and the last one moves the boat.
+        assert_eq!(
+            u64::MAX - 1,
+            Race::new(u64::MAX, 0).record_beating_possibilities()
+        );
+
+        // Holding for 1ms covers `T - 1` millimeters, which is not enough, but
+        // holding for 2ms already covers `2 * (T - 2)` millimeters.
+        assert_eq!(
+            u64::MAX - 3,
+            Race::new(u64::MAX, u64::MAX).record_beating_possibilities()
+        );
+
+        // Only holding for exactly half of the time covers `2^64` millimeters,
+        // which is one more than fits into a `u64`.
+        assert_eq!(
+            1,
+            Race::new(1 << 33, u64::MAX).record_beating_possibilities()
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_closed_form_matches_enumeration(total_time in 0..2_000u64, record_distance in 0..1_100_000u64) {
+            let race = Race::new(total_time, record_distance);
+            prop_assert_eq!(
+                race.record_beating_possibilities_by_enumeration(),
+                race.record_beating_possibilities()
+            );
+        }
+
+        #[test]
+        fn test_closed_form_matches_enumeration_near_boundary(total_time in 0..2_000u64, offset in 0..3u64) {
+            // Records just around the best possible distance `(T/2) * (T - T/2)`.
+            let best_distance = (total_time / 2) * (total_time - total_time / 2);
+            let race = Race::new(total_time, best_distance.saturating_sub(offset));
+            prop_assert_eq!(
+                race.record_beating_possibilities_by_enumeration(),
+                race.record_beating_possibilities()
+            );
+        }
+    }
+}
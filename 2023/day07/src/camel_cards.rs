@@ -0,0 +1,387 @@
+use anyhow::Context;
+use aoc_parse::ParseError;
+use derive_more::From;
+use itertools::Itertools;
+use nom::{
+    character::complete::anychar,
+    combinator::{all_consuming, map_res},
+    multi::count,
+    IResult,
+};
+use parse_display::FromStr;
+use std::str;
+
+/// The number of cards in a hand.
+const HAND_SIZE: usize = 5;
+
+#[derive(Debug, PartialEq, Eq, FromStr)]
+#[display("{cards} {bid}")]
+pub struct HandWithBid {
+    cards: Cards,
+    bid: usize,
+}
+
+/// The cards that make up one hand.
+#[derive(Debug, From, PartialEq, Eq)]
+pub struct Cards([Card; HAND_SIZE]);
+
+impl Cards {
+    /// Parse from `{Card}{Card}{Card}{Card}{Card}`.
+    fn parse(input: &str) -> IResult<&str, Self> {
+        let card_parser = map_res(anychar, |c| c.to_string().as_str().parse::<Card>());
+        let card_vec_parser = all_consuming(count(card_parser, HAND_SIZE));
+        let mut card_array_parser = map_res(card_vec_parser, Vec::try_into);
+
+        let (input, cards) = card_array_parser(input)?;
+
+        Ok((input, Self(cards)))
+    }
+}
+
+impl str::FromStr for Cards {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        aoc_parse::parse_all(s, Self::parse)
+    }
+}
+
+#[derive(FromStr, Debug, PartialEq, Eq, Hash, Clone, Copy)]
+#[display(style = "UPPERCASE")]
+pub enum Card {
+    #[display("{0}")]
+    #[from_str(regex = "(?<0>[2-9])")]
+    Number(u8),
+    T,
+    J,
+    Q,
+    K,
+    A,
+}
+
+impl Card {
+    /// All cards, from the weakest to the strongest.
+    const ALL: [Card; 13] = [
+        Card::Number(2),
+        Card::Number(3),
+        Card::Number(4),
+        Card::Number(5),
+        Card::Number(6),
+        Card::Number(7),
+        Card::Number(8),
+        Card::Number(9),
+        Card::T,
+        Card::J,
+        Card::Q,
+        Card::K,
+        Card::A,
+    ];
+}
+
+/// The types a hand can have. Which of them are played, and how they rank
+/// against each other, is up to the `Rules`. Camel Cards have no suits, so
+/// there are no flushes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    /// Five cards that directly follow each other in the card order.
+    Straight,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl HandType {
+    /// Check whether `cards`, which contain no wildcards, make up this hand
+    /// type. Hands also make up every hand type they contain, e.g. a full house
+    /// is a pair, too, which is why the strongest hand type counts.
+    fn is_made_up_by(&self, cards: &[Card; HAND_SIZE], rules: &Rules) -> bool {
+        // The numbers of occurrences of each card, from the most to the least
+        // frequent one.
+        let counts: Vec<usize> = cards
+            .iter()
+            .counts()
+            .into_values()
+            .sorted_unstable_by(|a, b| b.cmp(a))
+            .collect();
+        let count = |i: usize| counts.get(i).copied().unwrap_or(0);
+
+        match self {
+            HandType::HighCard => true,
+            HandType::OnePair => count(0) >= 2,
+            HandType::TwoPair => count(0) >= 2 && count(1) >= 2,
+            HandType::ThreeOfAKind => count(0) >= 3,
+            HandType::Straight => {
+                let ranks: Vec<usize> = cards
+                    .iter()
+                    .filter_map(|card| rules.rank(card))
+                    .sorted_unstable()
+                    .collect();
+                counts.len() == HAND_SIZE
+                    && ranks.len() == HAND_SIZE
+                    && ranks.iter().tuple_windows().all(|(a, b)| a + 1 == *b)
+            }
+            HandType::FullHouse => count(0) >= 3 && count(1) >= 2,
+            HandType::FourOfAKind => count(0) >= 4,
+            HandType::FiveOfAKind => count(0) >= 5,
+        }
+    }
+}
+
+/// The rules of a game of Camel Cards.
+#[derive(Debug, Clone)]
+pub struct Rules {
+    /// The cards in play, from the weakest to the strongest. Hands of the same
+    /// type are ranked by comparing their cards in this order.
+    card_order: Vec<Card>,
+    /// The cards that act like whichever card makes the strongest hand type.
+    /// For ranking hands of the same type, they keep their own place in the
+    /// card order.
+    wildcards: Vec<Card>,
+    /// The hand types in play, from the weakest to the strongest.
+    hand_types: Vec<HandType>,
+}
+
+impl Rules {
+    /// The rules the Elf explains first.
+    pub fn camel_cards() -> Self {
+        Self {
+            card_order: Card::ALL.to_vec(),
+            wildcards: Vec::new(),
+            hand_types: vec![
+                HandType::HighCard,
+                HandType::OnePair,
+                HandType::TwoPair,
+                HandType::ThreeOfAKind,
+                HandType::FullHouse,
+                HandType::FourOfAKind,
+                HandType::FiveOfAKind,
+            ],
+        }
+    }
+
+    /// The rules with `J` cards as jokers, which act as wildcards, but are the
+    /// weakest individual cards.
+    pub fn camel_cards_with_jokers() -> Self {
+        let card_order = [Card::J]
+            .into_iter()
+            .chain(Card::ALL.into_iter().filter(|&card| card != Card::J))
+            .collect();
+
+        Self {
+            card_order,
+            wildcards: vec![Card::J],
+            ..Self::camel_cards()
+        }
+    }
+
+    /// Additionally play `hand_type`, ranked directly above `weaker`. Returns
+    /// `None` if `weaker` is not in play.
+    pub fn with_hand_type_above(mut self, hand_type: HandType, weaker: HandType) -> Option<Self> {
+        self.hand_types.retain(|&t| t != hand_type);
+        let weaker_index = self.hand_types.iter().position(|&t| t == weaker)?;
+        self.hand_types.insert(weaker_index + 1, hand_type);
+        Some(self)
+    }
+
+    /// The place of a `card` in the card order, if it is in play.
+    fn rank(&self, card: &Card) -> Option<usize> {
+        self.card_order.iter().position(|other| other == card)
+    }
+
+    /// The strongest hand type the `cards` make up, where each wildcard is
+    /// replaced by the card that is best for the whole hand.
+    pub fn hand_type(&self, cards: &Cards) -> HandType {
+        let wildcard_positions: Vec<usize> = cards
+            .0
+            .iter()
+            .positions(|card| self.wildcards.contains(card))
+            .collect();
+
+        // Only straights can need cards that are not in the hand yet. For all
+        // other hand types, the wildcards are best spent on copies of the
+        // other cards, or on any card at all if they are all wildcards.
+        let candidates: Vec<&Card> = if self.hand_types.contains(&HandType::Straight) {
+            self.card_order.iter().collect()
+        } else {
+            let others: Vec<&Card> = cards
+                .0
+                .iter()
+                .filter(|card| !self.wildcards.contains(card))
+                .unique()
+                .collect();
+            match others.is_empty() {
+                true => self.card_order.iter().take(1).collect(),
+                false => others,
+            }
+        };
+
+        // Replacing the wildcards with every combination of candidates is
+        // enough, since the order of the cards does not matter for the type.
+        candidates
+            .into_iter()
+            .combinations_with_replacement(wildcard_positions.len())
+            .filter_map(|replacements| {
+                let mut replaced = cards.0;
+                for (&position, &&card) in wildcard_positions.iter().zip(&replacements) {
+                    replaced[position] = card;
+                }
+                self.hand_types
+                    .iter()
+                    .rposition(|hand_type| hand_type.is_made_up_by(&replaced, self))
+            })
+            .max()
+            .map(|index| self.hand_types[index])
+            .unwrap_or(HandType::HighCard)
+    }
+
+    /// The strength of a hand, which hands are ranked by: First by the rank
+    /// of their hand type, then by the ranks of their cards, one by one.
+    fn strength(&self, cards: &Cards) -> anyhow::Result<(usize, [usize; HAND_SIZE])> {
+        let hand_type = self.hand_type(cards);
+        let hand_type_rank = self
+            .hand_types
+            .iter()
+            .position(|&t| t == hand_type)
+            .context("every hand should be at least a high card")?;
+
+        let mut card_ranks = [0; HAND_SIZE];
+        for (card_rank, card) in card_ranks.iter_mut().zip(&cards.0) {
+            *card_rank = self
+                .rank(card)
+                .with_context(|| format!("card {:?} is not in play", card))?;
+        }
+
+        Ok((hand_type_rank, card_ranks))
+    }
+}
+
+/// Parse one hand with its bid per line.
+pub fn parse_hands(puzzle_input: &str) -> anyhow::Result<Vec<HandWithBid>> {
+    let hands = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(hands)
+}
+
+/// Rank the `hands` according to the `rules`, and sum up each hand's bid
+/// multiplied by its rank.
+pub fn total_winnings(hands: &[HandWithBid], rules: &Rules) -> anyhow::Result<usize> {
+    let strengths = hands
+        .iter()
+        .map(|hand| rules.strength(&hand.cards))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    let total_winnings = hands
+        .iter()
+        .zip(strengths)
+        .sorted_by(|(_, a), (_, b)| a.cmp(b))
+        // Give each hand a rank, which starts at 1, not 0.
+        .enumerate()
+        .map(|(i, (hand, _))| hand.bid * (i + 1))
+        .sum();
+
+    Ok(total_winnings)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cards(s: &str) -> Cards {
+        s.parse().expect("should parse")
+    }
+
+    #[test]
+    fn test_parse_hand_with_bid() {
+        let cards: Cards = [
+            Card::Number(3),
+            Card::Number(2),
+            Card::T,
+            Card::Number(3),
+            Card::K,
+        ]
+        .into();
+        let hand_with_bid = HandWithBid { cards, bid: 765 };
+        assert_eq!(hand_with_bid, "32T3K 765".parse().expect("should parse"));
+    }
+
+    #[test]
+    fn test_parse_card() {
+        assert_eq!(Card::A, "A".parse().expect("should parse"));
+        assert!("a".parse::<Card>().is_err());
+        assert!("1".parse::<Card>().is_err());
+
+        assert_eq!(Card::Number(9), "9".parse().expect("should parse"));
+    }
+
+    #[test]
+    fn test_hand_type() {
+        let rules = Rules::camel_cards();
+
+        assert_eq!(HandType::FiveOfAKind, rules.hand_type(&cards("AAAAA")));
+        assert_eq!(HandType::FourOfAKind, rules.hand_type(&cards("KTTTT")));
+        assert_eq!(HandType::FullHouse, rules.hand_type(&cards("KTKTT")));
+        assert_eq!(HandType::ThreeOfAKind, rules.hand_type(&cards("AAAKT")));
+        assert_eq!(HandType::TwoPair, rules.hand_type(&cards("AAKKT")));
+        assert_eq!(HandType::OnePair, rules.hand_type(&cards("AAT9K")));
+        assert_eq!(HandType::HighCard, rules.hand_type(&cards("23456")));
+
+        // Without jokers, `J` is an ordinary card.
+        assert_eq!(HandType::TwoPair, rules.hand_type(&cards("AAJJT")));
+    }
+
+    #[test]
+    fn test_hand_type_with_jokers() {
+        let rules = Rules::camel_cards_with_jokers();
+
+        // AAJJA => AAAAA
+        assert_eq!(HandType::FiveOfAKind, rules.hand_type(&cards("AAJJA")));
+        // JJJJJ => EEEEE (where E is any valid Card)
+        assert_eq!(HandType::FiveOfAKind, rules.hand_type(&cards("JJJJJ")));
+        // EJJJJ => EEEEE
+        assert_eq!(HandType::FiveOfAKind, rules.hand_type(&cards("AJJJJ")));
+        // KTJJT => KTTTT
+        assert_eq!(HandType::FourOfAKind, rules.hand_type(&cards("KTJJT")));
+        // KTJJJ => KTTTT
+        assert_eq!(HandType::FourOfAKind, rules.hand_type(&cards("KTJJJ")));
+        // KTKJT => KTK(K|T)T
+        assert_eq!(HandType::FullHouse, rules.hand_type(&cards("KTKJT")));
+        // AJJKT => AAAKT | AKKKT | ATTKT
+        assert_eq!(HandType::ThreeOfAKind, rules.hand_type(&cards("AJJKT")));
+        // AKT9J => AKT9A | AKT9K | AKT9T
+        assert_eq!(HandType::OnePair, rules.hand_type(&cards("AKT9J")));
+    }
+
+    #[test]
+    fn test_hand_type_with_straights() {
+        let rules = Rules::camel_cards_with_jokers()
+            .with_hand_type_above(HandType::Straight, HandType::ThreeOfAKind)
+            .expect("three of a kind should be in play");
+
+        assert_eq!(HandType::Straight, rules.hand_type(&cards("65478")));
+        assert_eq!(HandType::Straight, rules.hand_type(&cards("TQKA9")));
+        // The joker fills the gap, which beats making three of a kind.
+        assert_eq!(HandType::Straight, rules.hand_type(&cards("TJJKA")));
+        assert_eq!(HandType::HighCard, rules.hand_type(&cards("2345A")));
+        // A full house still beats a straight.
+        assert_eq!(HandType::FullHouse, rules.hand_type(&cards("KKJTT")));
+
+        // Straights are not in play by default.
+        let rules = Rules::camel_cards();
+        assert_eq!(HandType::HighCard, rules.hand_type(&cards("65478")));
+    }
+
+    #[test]
+    fn test_total_winnings() -> anyhow::Result<()> {
+        let hands = parse_hands("32T3K 765\nT55J5 684\nKK677 28\nKTJJT 220\nQQQJA 483")?;
+
+        assert_eq!(6440, total_winnings(&hands, &Rules::camel_cards())?);
+        assert_eq!(
+            5905,
+            total_winnings(&hands, &Rules::camel_cards_with_jokers())?
+        );
+
+        Ok(())
+    }
+}
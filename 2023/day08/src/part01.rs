@@ -1,19 +1,30 @@
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_more::From;
-use parse_display::{FromStr, ParseError};
+use parse_display::FromStr;
 use std::{collections::HashMap, str};
 
 #[derive(Debug)]
-struct Instructions(Vec<Instruction>);
+pub struct Instructions(Vec<Instruction>);
 
 // TODO: this feels like boilerplate some crate should generate
 impl str::FromStr for Instructions {
-    type Err = anyhow::Error;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let insts: Vec<Instruction> = s
-            .chars()
-            .map(|c| c.to_string().as_str().parse())
-            .collect::<Result<_, ParseError>>()?;
+            .char_indices()
+            .map(|(offset, c)| {
+                c.to_string()
+                    .parse()
+                    .map_err(|_| ParseError::new(s, offset, "expected `L` or `R`"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if insts.is_empty() {
+            return Err(ParseError::new(s, 0, "expected at least one instruction"));
+        }
+
         Ok(Self(insts))
     }
 }
@@ -28,7 +39,7 @@ enum Instruction {
 
 /// A tree structure of `Node`s.
 #[derive(Debug)]
-struct Network(HashMap<Node, (Node, Node)>);
+pub struct Network(HashMap<Node, (Node, Node)>);
 
 impl FromIterator<NodeConnection> for Network {
     fn from_iter<T: IntoIterator<Item = NodeConnection>>(iter: T) -> Self {
@@ -41,18 +52,34 @@ impl FromIterator<NodeConnection> for Network {
 }
 
 impl Network {
+    /// Make sure that all nodes lead to nodes of the network, so that every
+    /// traversal is valid.
+    fn validate(&self) -> anyhow::Result<()> {
+        for (from, (left, right)) in &self.0 {
+            for to in [left, right] {
+                ensure!(
+                    self.0.contains_key(to),
+                    "node {} leads to the unknown node {}",
+                    from.0,
+                    to.0
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn traverse_left(&self, from: &Node) -> &Node {
         self.0
             .get(from)
             .map(|(left, _right)| left)
-            .expect("all instructed traversals should be valid")
+            .expect("the network was validated to only lead to its own nodes")
     }
 
     fn traverse_right(&self, from: &Node) -> &Node {
         self.0
             .get(from)
             .map(|(_left, right)| right)
-            .expect("all instructed traversals should be valid")
+            .expect("the network was validated to only lead to its own nodes")
     }
 }
 
@@ -67,20 +94,39 @@ struct NodeConnection {
 #[derive(Debug, FromStr, From, Clone, Hash, PartialEq, Eq)]
 struct Node(String);
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<(Instructions, Network)> {
     let mut lines = puzzle_input.lines();
 
-    // TODO: don't use unwrap() to parse, use nom instead
-    let instructions: Instructions = lines.next().unwrap().parse()?;
+    let instructions: Instructions = lines
+        .next()
+        .context("missing instructions in puzzle input")?
+        .parse()?;
 
     // New line between `Instructions` and `NodeConnection`s.
-    lines.next().unwrap();
+    lines.next();
+
+    let network: Network = lines
+        .enumerate()
+        .map(|(line_index, line)| {
+            // The node connections start at the third line.
+            line.parse::<NodeConnection>()
+                .map_err(|err| ParseError::from(err).in_line(line_index + 2, line))
+        })
+        .collect::<Result<_, _>>()?;
+    network.validate()?;
 
-    let node_connections = lines.map(|line| line.parse::<NodeConnection>().unwrap());
-    let network: Network = node_connections.collect();
+    Ok((instructions, network))
+}
 
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed((instructions, network): (Instructions, Network)) -> anyhow::Result<Solution> {
     let start_node = &Node::from("AAA".to_string());
     let end_node = &Node::from("ZZZ".to_string());
+    ensure!(
+        network.0.contains_key(start_node),
+        "the network does not contain the starting node AAA"
+    );
     let mut cur_node = start_node;
 
     let steps_to_reach_end: usize = instructions
@@ -102,5 +148,10 @@ pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
         })
         .expect("infinitely cycled iterator cannot terminate without finding something");
 
-    Ok(steps_to_reach_end.to_string())
+    Ok(steps_to_reach_end.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
@@ -1,21 +1,34 @@
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_more::From;
+#[cfg(test)]
+use indoc::indoc;
 use itertools::Itertools;
-use num_integer::Integer;
-use parse_display::{FromStr, ParseError};
+use num_integer::{ExtendedGcd, Integer};
+use parse_display::FromStr;
 use std::{collections::HashMap, str};
 
 #[derive(Debug)]
-struct Instructions(Vec<Instruction>);
+pub struct Instructions(Vec<Instruction>);
 
 // TODO: this feels like boilerplate some crate should generate
 impl str::FromStr for Instructions {
-    type Err = anyhow::Error;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let insts: Vec<Instruction> = s
-            .chars()
-            .map(|c| c.to_string().as_str().parse())
-            .collect::<Result<_, ParseError>>()?;
+            .char_indices()
+            .map(|(offset, c)| {
+                c.to_string()
+                    .parse()
+                    .map_err(|_| ParseError::new(s, offset, "expected `L` or `R`"))
+            })
+            .collect::<Result<_, _>>()?;
+
+        if insts.is_empty() {
+            return Err(ParseError::new(s, 0, "expected at least one instruction"));
+        }
+
         Ok(Self(insts))
     }
 }
@@ -30,7 +43,7 @@ enum Instruction {
 
 /// A tree structure of `Node`s.
 #[derive(Debug)]
-struct Network(HashMap<Node, (Node, Node)>);
+pub struct Network(HashMap<Node, (Node, Node)>);
 
 impl FromIterator<NodeConnection> for Network {
     fn from_iter<T: IntoIterator<Item = NodeConnection>>(iter: T) -> Self {
@@ -43,22 +56,45 @@ impl FromIterator<NodeConnection> for Network {
 }
 
 impl Network {
+    /// Make sure that all nodes lead to nodes of the network, so that every
+    /// traversal is valid.
+    fn validate(&self) -> anyhow::Result<()> {
+        for (from, (left, right)) in &self.0 {
+            for to in [left, right] {
+                ensure!(
+                    self.0.contains_key(to),
+                    "node {} leads to the unknown node {}",
+                    from.internal,
+                    to.internal
+                );
+            }
+        }
+        Ok(())
+    }
+
     fn find_starting_nodes(&self) -> impl Iterator<Item = &Node> {
         self.0.keys().filter(|node| node.is_starting_node())
     }
 
+    fn traverse(&self, from: &Node, inst: &Instruction) -> &Node {
+        match inst {
+            Instruction::Left => self.traverse_left(from),
+            Instruction::Right => self.traverse_right(from),
+        }
+    }
+
     fn traverse_left(&self, from: &Node) -> &Node {
         self.0
             .get(from)
             .map(|(left, _right)| left)
-            .expect("all instructed traversals should be valid")
+            .expect("the network was validated to only lead to its own nodes")
     }
 
     fn traverse_right(&self, from: &Node) -> &Node {
         self.0
             .get(from)
             .map(|(_left, right)| right)
-            .expect("all instructed traversals should be valid")
+            .expect("the network was validated to only lead to its own nodes")
     }
 }
 
@@ -111,8 +147,10 @@ enum NodeType {
 }
 
 /// This naive solution iterates from the starting nodes until all end nodes are
-/// found at the same time. Unfortunately, this solution is incredibly slow.
-fn _brute_force_solution(instructions: Instructions, network: Network) -> usize {
+/// found at the same time. Unfortunately, this solution is incredibly slow, so
+/// it only serves as a reference for testing the other solutions.
+#[cfg(test)]
+fn brute_force_solution(instructions: &Instructions, network: &Network) -> usize {
     let starting_nodes = network.find_starting_nodes().collect_vec();
     let mut cur_nodes = starting_nodes;
 
@@ -131,11 +169,7 @@ fn _brute_force_solution(instructions: Instructions, network: Network) -> usize
                 // Always iterate to next node, regardless of whether we are an
                 // ending node, since our other `cur_nodes` might not all be
                 // ending nodes, in which case we must continue searching.
-                let next_node = match inst {
-                    Instruction::Left => network.traverse_left(cur_node),
-                    Instruction::Right => network.traverse_right(cur_node),
-                };
-                *cur_node = next_node;
+                *cur_node = network.traverse(cur_node, inst);
             }
 
             all_end_nodes.then_some(i)
@@ -157,54 +191,279 @@ fn test_lcm() {
     assert_eq!(5 * 7 * 11, lcm([5, 7, 11]));
 }
 
-/// With this solution, we use the fact that the all paths from any starting
-/// node to its end node are repeating. Now, we only have to compute the length
-/// of the cycle of each starting node to end node path, and check when the
-/// total step count of all cycles line up (by calculating the `Largest Common
-/// Multiplier`, short `lcm`, of all cycles).
-fn lcm_of_cycles_solution(instructions: Instructions, network: Network) -> usize {
-    let starting_nodes = network.find_starting_nodes().collect_vec();
-    let mut cur_nodes = starting_nodes;
+/// The set of all steps `remainder + k * modulus` for any integer `k`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Congruence {
+    remainder: i128,
+    modulus: i128,
+}
 
-    let cycles_iter = cur_nodes.iter_mut().map(|cur_node| {
-        instructions
-            .0
-            .iter()
-            .cycle()
-            .enumerate()
-            .find_map(|(i, inst)| {
-                let is_ending_node = cur_node.is_ending_node();
+impl Congruence {
+    fn new(remainder: usize, modulus: usize) -> Self {
+        let modulus = modulus as i128;
+        Self {
+            remainder: (remainder as i128).rem_euclid(modulus),
+            modulus,
+        }
+    }
 
-                // Always iterate to next node, regardless of whether we are an
-                // ending node, since our other `cur_nodes` might not all be
-                // ending nodes, in which case we must continue searching.
-                let next_node = match inst {
-                    Instruction::Left => network.traverse_left(cur_node),
-                    Instruction::Right => network.traverse_right(cur_node),
+    /// Combine two congruences into the one describing the steps both of them
+    /// contain, using the generalized `Chinese Remainder Theorem`, which also
+    /// works for moduli that are not coprime. Return `None` if no step is
+    /// contained in both.
+    fn combine(self, other: Self) -> Option<Self> {
+        let ExtendedGcd { gcd, x, .. } = self.modulus.extended_gcd(&other.modulus);
+
+        let difference = other.remainder - self.remainder;
+        if difference % gcd != 0 {
+            return None;
+        }
+
+        // `x * self.modulus ≡ gcd (mod other.modulus)`, so stepping
+        // `difference / gcd * x` multiples of `self.modulus` ahead of
+        // `self.remainder` also satisfies `other`.
+        let modulus = self.modulus / gcd * other.modulus;
+        let multiples = (difference / gcd * x).rem_euclid(other.modulus / gcd);
+        Some(Self {
+            remainder: (self.remainder + multiples * self.modulus).rem_euclid(modulus),
+            modulus,
+        })
+    }
+
+    /// The smallest step in this congruence that is at least `min_step`.
+    fn smallest_at_least(&self, min_step: usize) -> usize {
+        let min_step = min_step as i128;
+        let steps_short = (min_step - self.remainder).max(0);
+        let periods = (steps_short + self.modulus - 1) / self.modulus;
+        (self.remainder + periods * self.modulus) as usize
+    }
+}
+
+#[test]
+fn test_congruence() {
+    // 2 (mod 3) and 3 (mod 5) is 8 (mod 15).
+    let combined = Congruence::new(2, 3).combine(Congruence::new(3, 5));
+    assert_eq!(Some(Congruence::new(8, 15)), combined);
+
+    // Non-coprime moduli: 3 (mod 4) and 5 (mod 6) is 11 (mod 12).
+    let combined = Congruence::new(3, 4).combine(Congruence::new(5, 6));
+    assert_eq!(Some(Congruence::new(11, 12)), combined);
+
+    // Even and odd steps never line up.
+    assert_eq!(None, Congruence::new(0, 2).combine(Congruence::new(1, 4)));
+
+    assert_eq!(8, Congruence::new(8, 15).smallest_at_least(0));
+    assert_eq!(23, Congruence::new(8, 15).smallest_at_least(9));
+    assert_eq!(23, Congruence::new(8, 15).smallest_at_least(23));
+}
+
+/// The path a single ghost takes from its starting node. Since the ghost's next
+/// move only depends on its current node and instruction index, and there are
+/// only finitely many of those states, every path ends up in a cycle after
+/// some lead-in.
+#[derive(Debug, PartialEq, Eq)]
+struct GhostPath {
+    /// The number of steps before the ghost enters its cycle.
+    prefix_len: usize,
+    cycle_len: usize,
+    /// The steps before entering the cycle at which the ghost is on an ending
+    /// node.
+    prefix_hits: Vec<usize>,
+    /// The steps during the first pass through the cycle at which the ghost
+    /// is on an ending node. These repeat every `cycle_len` steps.
+    cycle_hits: Vec<usize>,
+}
+
+impl GhostPath {
+    /// Follow the `instructions` from the `starting_node` until a state repeats.
+    fn trace(instructions: &Instructions, network: &Network, starting_node: &Node) -> Self {
+        let mut first_seen_at: HashMap<(&Node, usize), usize> = HashMap::new();
+        let mut hits = Vec::new();
+        let mut cur_node = starting_node;
+
+        for (step, (inst_idx, inst)) in instructions.0.iter().enumerate().cycle().enumerate() {
+            if let Some(&prefix_len) = first_seen_at.get(&(cur_node, inst_idx)) {
+                let (prefix_hits, cycle_hits) = hits.iter().partition(|&&hit| hit < prefix_len);
+                return Self {
+                    prefix_len,
+                    cycle_len: step - prefix_len,
+                    prefix_hits,
+                    cycle_hits,
                 };
-                *cur_node = next_node;
+            }
+            first_seen_at.insert((cur_node, inst_idx), step);
 
-                is_ending_node.then_some(i)
-            })
-            .expect("infinitely cycled iterator cannot terminate without finding something")
-    });
+            if cur_node.is_ending_node() {
+                hits.push(step);
+            }
+            cur_node = network.traverse(cur_node, inst);
+        }
+
+        unreachable!("infinitely cycled iterator cannot terminate without finding a cycle")
+    }
 
-    lcm(cycles_iter)
+    /// Whether the ghost is on an ending node after `step` steps.
+    fn is_hit(&self, step: usize) -> bool {
+        if step < self.prefix_len {
+            self.prefix_hits.contains(&step)
+        } else {
+            let step_in_first_cycle = self.prefix_len + (step - self.prefix_len) % self.cycle_len;
+            self.cycle_hits.contains(&step_in_first_cycle)
+        }
+    }
+
+    /// Whether the ghost is on an ending node exactly every `cycle_len` steps,
+    /// and never otherwise. This is the case for all ghosts of the puzzle
+    /// inputs, but isn't guaranteed by the puzzle description.
+    fn only_hits_at_cycle_multiples(&self) -> bool {
+        self.prefix_hits.is_empty() && self.cycle_hits == [self.cycle_len]
+    }
+
+    /// The steps at which the ghost is on an ending node during its cycle.
+    fn cycle_congruences(&self) -> impl Iterator<Item = Congruence> + Clone + '_ {
+        self.cycle_hits
+            .iter()
+            .map(|&hit| Congruence::new(hit, self.cycle_len))
+    }
+}
+
+/// With this solution, we detect the cycle every ghost ends up in, together
+/// with the steps at which it is on an ending node. If every ghost is on an
+/// ending node exactly at multiples of its cycle length, all ghosts line up at
+/// the `Largest Common Multiplier`, short `lcm`, of all cycle lengths.
+/// Otherwise, the ghosts line up either at a step before some ghost enters its
+/// cycle, which we check directly, or at a step where all ghosts are in their
+/// cycles, which we find with the `Chinese Remainder Theorem`. Return `None` if
+/// the ghosts never line up.
+fn cycles_solution(instructions: &Instructions, network: &Network) -> Option<usize> {
+    let ghost_paths = network
+        .find_starting_nodes()
+        .map(|starting_node| GhostPath::trace(instructions, network, starting_node))
+        .collect_vec();
+
+    if ghost_paths
+        .iter()
+        .all(GhostPath::only_hits_at_cycle_multiples)
+    {
+        return Some(lcm(ghost_paths.iter().map(|path| path.cycle_len)));
+    }
+
+    let all_hit = |step: usize| ghost_paths.iter().all(|path| path.is_hit(step));
+
+    let before_some_cycle = ghost_paths
+        .iter()
+        .flat_map(|path| path.prefix_hits.iter().copied())
+        .filter(|&step| all_hit(step))
+        .min();
+
+    let all_in_cycles_from = ghost_paths
+        .iter()
+        .map(|path| path.prefix_len)
+        .max()
+        .unwrap_or(0);
+    let all_in_cycles = ghost_paths
+        .iter()
+        .map(GhostPath::cycle_congruences)
+        .multi_cartesian_product()
+        .filter_map(|congruences| {
+            congruences
+                .into_iter()
+                .try_fold(Congruence::new(0, 1), Congruence::combine)
+        })
+        .map(|congruence| congruence.smallest_at_least(all_in_cycles_from))
+        .min();
+
+    before_some_cycle.into_iter().chain(all_in_cycles).min()
 }
 
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
+#[test]
+fn test_cycles_solution_with_lead_in_and_multiple_hits() -> Result<()> {
+    // The first ghost enters a cycle of length 4 after 2 steps, and is on an
+    // ending node after 3 and 5 steps. The second ghost is on an ending node
+    // after 1 step, before entering its cycle of length 4, and after 5 steps.
+    let puzzle_input = indoc! {"
+        L
+
+        11A = (11B, 11B)
+        11B = (11C, 11C)
+        11C = (11Z, 11Z)
+        11Z = (11D, 11D)
+        11D = (1EZ, 1EZ)
+        1EZ = (11C, 11C)
+        22A = (22Z, 22Z)
+        22Z = (22B, 22B)
+        22B = (22C, 22C)
+        22C = (22D, 22D)
+        22D = (2EZ, 2EZ)
+        2EZ = (22B, 22B)
+    "};
+    let (instructions, network) = parse(puzzle_input)?;
+
+    assert_eq!(5, brute_force_solution(&instructions, &network));
+    assert_eq!(Some(5), cycles_solution(&instructions, &network));
+
+    Ok(())
+}
+
+#[test]
+fn test_cycles_solution_without_solution() -> Result<()> {
+    // The first ghost is on an ending node after every odd number of steps, the
+    // second one only after every even number of steps.
+    let puzzle_input = indoc! {"
+        LR
+
+        11A = (11Z, 11Z)
+        11Z = (11A, 11A)
+        22A = (22B, 22B)
+        22B = (22Z, 22Z)
+        22Z = (22B, 22B)
+    "};
+    let (instructions, network) = parse(puzzle_input)?;
+
+    assert_eq!(None, cycles_solution(&instructions, &network));
+
+    Ok(())
+}
+
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<(Instructions, Network)> {
     let mut lines = puzzle_input.lines();
 
-    // TODO: don't use unwrap() to parse, use nom instead
-    let instructions: Instructions = lines.next().unwrap().parse()?;
+    let instructions: Instructions = lines
+        .next()
+        .context("missing instructions in puzzle input")?
+        .parse()?;
 
     // New line between `Instructions` and `NodeConnection`s.
-    lines.next().unwrap();
+    lines.next();
 
-    let node_connections = lines.map(|line| line.parse::<NodeConnection>().unwrap());
-    let network: Network = node_connections.collect();
+    let network: Network = lines
+        .enumerate()
+        .map(|(line_index, line)| {
+            // The node connections start at the third line.
+            line.parse::<NodeConnection>()
+                .map_err(|err| ParseError::from(err).in_line(line_index + 2, line))
+        })
+        .collect::<Result<_, _>>()?;
+    network.validate()?;
 
-    let steps_to_reach_end = lcm_of_cycles_solution(instructions, network);
+    Ok((instructions, network))
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed((instructions, network): (Instructions, Network)) -> anyhow::Result<Solution> {
+    ensure!(
+        network.find_starting_nodes().next().is_some(),
+        "the network contains no starting nodes"
+    );
+
+    let steps_to_reach_end = cycles_solution(&instructions, &network)
+        .context("the ghosts are never all on ending nodes at the same time")?;
+
+    Ok(steps_to_reach_end.into())
+}
 
-    Ok(steps_to_reach_end.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
@@ -0,0 +1,257 @@
+use anyhow::{ensure, Context};
+use aoc_parse::ParseError;
+use num_rational::Ratio;
+use std::str;
+
+pub type HistoryValue = i64;
+
+/// The values of a history, and of everything extrapolated from it, are
+/// computed with this wider type, so that far extrapolations do not overflow.
+pub type ExtrapolatedValue = i128;
+
+#[derive(Debug, Clone)]
+pub struct History(Vec<HistoryValue>);
+
+// TODO: boilerplate, replace with parse_display's parsing on delimiter (here ' ') once that's ready
+impl str::FromStr for History {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let history = s
+            .split(' ')
+            .map(|value| {
+                value
+                    .parse()
+                    .map_err(|err| ParseError::at(s, value, format!("invalid value: {}", err)))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self(history))
+    }
+}
+
+impl History {
+    /// Find the polynomial of lowest degree that produces this history, where
+    /// the first value is produced at `x = 0`, the second one at `x = 1` etc.
+    ///
+    /// Repeatedly taking the differences between the values of the history
+    /// must eventually produce a sequence of zeros. Otherwise, e.g. for
+    /// `1 2 4`, there are too few values to tell which polynomial produces
+    /// them, so this fails.
+    pub fn polynomial(&self) -> anyhow::Result<Polynomial> {
+        ensure!(!self.0.is_empty(), "the history is empty");
+
+        // Instead of building the whole triangle of differences, compute it
+        // in place: After round `k`, the values from index `k` onwards are the
+        // `k`-th differences, so the `k`-th value is the first of them.
+        let mut forward_differences: Vec<ExtrapolatedValue> = self
+            .0
+            .iter()
+            .copied()
+            .map(ExtrapolatedValue::from)
+            .collect();
+        for round in 1..forward_differences.len() {
+            for i in (round..forward_differences.len()).rev() {
+                forward_differences[i] = forward_differences[i]
+                    .checked_sub(forward_differences[i - 1])
+                    .context("the differences of the history overflow")?;
+            }
+        }
+
+        // The last differences consist of a single value. Only if it is zero
+        // have the differences reached a sequence of zeros.
+        ensure!(
+            forward_differences.last() == Some(&0),
+            "the differences of the history never reach all zeros"
+        );
+
+        let degree = forward_differences
+            .iter()
+            .rposition(|&difference| difference != 0);
+        forward_differences.truncate(degree.map_or(0, |degree| degree + 1));
+
+        Ok(Polynomial {
+            forward_differences,
+        })
+    }
+
+    /// Extrapolate/predict the value `steps` steps after the last value of
+    /// this history.
+    pub fn extrapolate_forward(&self, steps: usize) -> anyhow::Result<ExtrapolatedValue> {
+        let polynomial = self.polynomial()?;
+        let x = (self.0.len() - 1)
+            .checked_add(steps)
+            .context("too many steps")?;
+        polynomial.value_at(ExtrapolatedValue::try_from(x)?)
+    }
+
+    /// Extrapolate/predict the value `steps` steps before the first value of
+    /// this history.
+    pub fn extrapolate_backward(&self, steps: usize) -> anyhow::Result<ExtrapolatedValue> {
+        let x = -ExtrapolatedValue::try_from(steps)?;
+        self.polynomial()?.value_at(x)
+    }
+}
+
+/// A polynomial with rational coefficients that produces whole numbers at
+/// all whole `x`, stored in Newton's forward difference form:
+/// `p(x) = Σ Δ^k p(0) * binomial(x, k)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    /// The `k`-th forward difference at `x = 0`, for all `k` up to the degree.
+    /// Empty for the zero polynomial.
+    forward_differences: Vec<ExtrapolatedValue>,
+}
+
+impl Polynomial {
+    /// The degree, or `None` for the zero polynomial.
+    pub fn degree(&self) -> Option<usize> {
+        self.forward_differences.len().checked_sub(1)
+    }
+
+    /// Evaluate the polynomial at a whole `x`, which may also lie before the
+    /// values it was fitted to.
+    pub fn value_at(&self, x: ExtrapolatedValue) -> anyhow::Result<ExtrapolatedValue> {
+        let overflow = || format!("the value at {} overflows", x);
+
+        let mut value: ExtrapolatedValue = 0;
+        // `binomial(x, k)`, which is a whole number even for negative `x`.
+        let mut binomial: ExtrapolatedValue = 1;
+
+        for (k, &difference) in self.forward_differences.iter().enumerate() {
+            let term = difference.checked_mul(binomial).with_context(overflow)?;
+            value = value.checked_add(term).with_context(overflow)?;
+
+            // binomial(x, k + 1) = binomial(x, k) * (x - k) / (k + 1)
+            let k = ExtrapolatedValue::try_from(k)?;
+            binomial = binomial.checked_mul(x - k).with_context(overflow)? / (k + 1);
+        }
+
+        Ok(value)
+    }
+
+    /// The exact coefficients `c` of `p(x) = c[0] + c[1] * x + c[2] * x^2 + ..`,
+    /// up to the degree.
+    pub fn coefficients(&self) -> anyhow::Result<Vec<Ratio<ExtrapolatedValue>>> {
+        let Some(degree) = self.degree() else {
+            return Ok(Vec::new());
+        };
+        let overflow = || "the coefficients overflow";
+
+        // binomial(x, k) = falling_factorial(x, k) / k!, so with all terms over
+        // the common denominator `degree!`, the `k`-th term is
+        // Δ^k p(0) * falling_factorial(x, k) * (degree! / k!).
+        let mut numerators: Vec<ExtrapolatedValue> = vec![0; degree + 1];
+        // The coefficients of `falling_factorial(x, k) = x (x - 1) .. (x - k + 1)`.
+        let mut falling_factorial: Vec<ExtrapolatedValue> = vec![1];
+
+        for (k, &difference) in self.forward_differences.iter().enumerate() {
+            let scale = (k + 1..=degree).try_fold(difference, |scale, factor| {
+                scale.checked_mul(ExtrapolatedValue::try_from(factor).ok()?)
+            });
+            let scale = scale.with_context(overflow)?;
+
+            for (numerator, &coefficient) in numerators.iter_mut().zip(&falling_factorial) {
+                let term = coefficient.checked_mul(scale).with_context(overflow)?;
+                *numerator = numerator.checked_add(term).with_context(overflow)?;
+            }
+
+            // Multiply by `(x - k)` for the next round.
+            let k = ExtrapolatedValue::try_from(k)?;
+            let mut next = vec![0; falling_factorial.len() + 1];
+            for (power, &coefficient) in falling_factorial.iter().enumerate() {
+                next[power + 1] += coefficient;
+                next[power] = coefficient
+                    .checked_mul(k)
+                    .and_then(|product| next[power].checked_sub(product))
+                    .with_context(overflow)?;
+            }
+            falling_factorial = next;
+        }
+
+        let denominator = (1..=degree).try_fold(1, |product: ExtrapolatedValue, factor| {
+            product.checked_mul(ExtrapolatedValue::try_from(factor).ok()?)
+        });
+        let denominator = denominator.with_context(overflow)?;
+
+        Ok(numerators
+            .into_iter()
+            .map(|numerator| Ratio::new(numerator, denominator))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn history(s: &str) -> History {
+        s.parse().expect("should parse")
+    }
+
+    fn ratios(
+        coefficients: &[(ExtrapolatedValue, ExtrapolatedValue)],
+    ) -> Vec<Ratio<ExtrapolatedValue>> {
+        coefficients
+            .iter()
+            .map(|&(numerator, denominator)| Ratio::new(numerator, denominator))
+            .collect()
+    }
+
+    #[test]
+    fn test_polynomial() -> anyhow::Result<()> {
+        // 3x
+        let polynomial = history("0 3 6 9 12 15").polynomial()?;
+        assert_eq!(Some(1), polynomial.degree());
+        assert_eq!(ratios(&[(0, 1), (3, 1)]), polynomial.coefficients()?);
+
+        // x^2 / 2 + 3x / 2 + 1
+        let polynomial = history("1 3 6 10 15 21").polynomial()?;
+        assert_eq!(Some(2), polynomial.degree());
+        assert_eq!(
+            ratios(&[(1, 1), (3, 2), (1, 2)]),
+            polynomial.coefficients()?
+        );
+
+        // x^3 - 2x + 10
+        let polynomial = history("10 9 14 31 66").polynomial()?;
+        assert_eq!(Some(3), polynomial.degree());
+        assert_eq!(
+            ratios(&[(10, 1), (-2, 1), (0, 1), (1, 1)]),
+            polynomial.coefficients()?
+        );
+
+        let polynomial = history("7 7 7").polynomial()?;
+        assert_eq!(Some(0), polynomial.degree());
+        assert_eq!(ratios(&[(7, 1)]), polynomial.coefficients()?);
+
+        let polynomial = history("0 0").polynomial()?;
+        assert_eq!(None, polynomial.degree());
+        assert!(polynomial.coefficients()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extrapolate() -> anyhow::Result<()> {
+        let history = history("10 13 16 21 30 45");
+        assert_eq!(68, history.extrapolate_forward(1)?);
+        assert_eq!(5, history.extrapolate_backward(1)?);
+        assert_eq!(45, history.extrapolate_forward(0)?);
+        assert_eq!(10, history.extrapolate_backward(0)?);
+
+        // .. -19 -4 5 [10 13 16 21 30 45] 68 101 146 ..
+        assert_eq!(101, history.extrapolate_forward(2)?);
+        assert_eq!(146, history.extrapolate_forward(3)?);
+        assert_eq!(-4, history.extrapolate_backward(2)?);
+        assert_eq!(-19, history.extrapolate_backward(3)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_differences_never_reaching_zero() {
+        assert!(history("1 2 4").polynomial().is_err());
+        assert!(history("5").extrapolate_forward(1).is_err());
+        assert!(history("0").extrapolate_forward(1).is_ok());
+        assert!(History(Vec::new()).extrapolate_forward(1).is_err());
+    }
+}
@@ -1,110 +1,99 @@
-use anyhow::bail;
+use anyhow::{bail, ensure, Context};
+use aoc_grid::{Direction, Grid, GridIndex, PositionInGrid};
+use aoc_solution::Solution;
 use derive_new::new;
-use ndarray::Array2;
 use parse_display::FromStr;
 use std::{cmp::Ordering, str};
-use strum::{EnumIter, IntoEnumIterator};
+use strum::IntoEnumIterator;
 
 #[derive(Debug)]
-struct TileGrid(Array2<TileInGrid>);
+pub struct TileGrid(Grid<TileInGrid>);
 
 impl str::FromStr for TileGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let rows = s.lines().count();
-        let cols = match s.lines().next() {
-            Some(first_row) => first_row.chars().count(),
-            None => bail!("Expected there to be at least one row, but found none."),
-        };
-
-        let grid_vec: Vec<_> = s
-            .lines()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.chars().enumerate().map(move |(col, c)| {
-                    // TODO: remove unwrap from parse
-                    let tile = Tile::from(c.to_string().as_str().parse::<TileParsed>().unwrap());
-                    TileInGrid::new(tile, row, col)
-                })
-            })
-            .collect();
-
-        let grid = Array2::from_shape_vec((rows, cols), grid_vec)?;
+        let grid = Grid::parse_with(s, |pos, c| {
+            let tile = Tile::from(c.to_string().as_str().parse::<TileParsed>()?);
+            Ok(TileInGrid::new(tile, pos.row, pos.col))
+        })?;
         Ok(Self(grid))
     }
 }
 
 impl TileGrid {
     /// Get the starting tile.
-    fn get_starting_tile(&self) -> &TileInGrid {
+    fn get_starting_tile(&self) -> anyhow::Result<&TileInGrid> {
         self.0
             .iter()
             .find(|tile| matches!(tile.tile, Tile::AnimalStartingPosition))
-            .expect("there must be a starting tile")
+            .context("there is no starting tile")
     }
 
     /// Find the tiles that make up the main loop. The main loop is a set of
     /// connected pipes that form a loop from the starting tile back to the
     /// starting tile.
-    fn find_main_loop(&self) -> Vec<&TileInGrid> {
-        let starting_tile = self.get_starting_tile();
+    ///
+    /// More than two neighboring pipes may point towards the starting tile, so
+    /// walk along the pipes from each of them, and pick the first walk that
+    /// actually gets back to the starting tile.
+    fn find_main_loop(&self) -> anyhow::Result<Vec<&TileInGrid>> {
+        let starting_tile = self.get_starting_tile()?;
 
-        let (initial_pipe, initial_dir) = self.find_initial_connected_pipes(starting_tile);
+        // Search every direction for connected pipes.
+        let initial_dirs: Vec<Direction> = Direction::iter()
+            .filter(|direction| {
+                starting_tile
+                    .get_next_in_direction(direction, self)
+                    .is_some_and(|pipe| pipe.is_connected_with_starting_tile(starting_tile))
+            })
+            .collect();
+        ensure!(
+            initial_dirs.len() >= 2,
+            "expected at least two pipes to be connected to the starting tile"
+        );
+
+        let mut first_err = None;
+        for initial_dir in initial_dirs {
+            match self.walk_loop(starting_tile, initial_dir) {
+                Ok(main_loop_tiles) => return Ok(main_loop_tiles),
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
 
-        // TODO: try using maybe fold for more idiomatic rust, also vec1 for nice .last()
+        Err(first_err.expect("there were pipes to walk along"))
+    }
 
+    /// Walk along the pipes, starting from the `starting_tile` in the
+    /// direction `initial_dir`, until getting back to the starting tile.
+    /// Return the tiles walked along, starting with the starting tile.
+    fn walk_loop<'a>(
+        &'a self,
+        starting_tile: &'a TileInGrid,
+        initial_dir: Direction,
+    ) -> anyhow::Result<Vec<&'a TileInGrid>> {
         let mut main_loop_tiles = vec![starting_tile];
 
-        let mut cur_tile = initial_pipe;
+        let mut cur_tile = starting_tile
+            .get_next_in_direction(&initial_dir, self)
+            .context("the main loop leaves the grid at the starting tile")?;
         let mut facing_dir = &initial_dir;
 
         while cur_tile != starting_tile {
             main_loop_tiles.push(cur_tile);
-            (cur_tile, facing_dir) = cur_tile.find_next_connected_pipe(facing_dir, self);
+            (cur_tile, facing_dir) = cur_tile.find_next_connected_pipe(facing_dir, self)?;
         }
 
-        main_loop_tiles
-    }
-
-    /// Find one of the pipes connected to the starting tile. Return the
-    /// direction in which it was found, relative to the starting pipe, along
-    /// with the connected pipe tile itself.
-    fn find_initial_connected_pipes<'a>(
-        &'a self,
-        starting_tile: &TileInGrid,
-    ) -> (&'a TileInGrid, Direction) {
-        // Search every direction for connected pipes.
-        let mut initial_pipes: Vec<(&TileInGrid, Direction)> = Direction::iter()
-            .filter_map(|direction| {
-                if let Some(potential_pipe) = starting_tile.get_next_in_direction(&direction, self)
-                {
-                    if potential_pipe.is_connected_with_starting_tile(starting_tile) {
-                        return Some((potential_pipe, direction));
-                    }
-                }
-                None
-            })
-            .collect();
-
-        // It doesn't matter which of the two initial pipes we choose to traverse.
-        let (Some((initial_pipe, initial_dir)), Some(_)) =
-            (initial_pipes.pop(), initial_pipes.pop())
-        else {
-            panic!("expected there to be exactly two pipes connected to the starting tile");
-        };
-
-        (initial_pipe, initial_dir)
+        Ok(main_loop_tiles)
     }
 }
 
-/// An index into a `ndarray::Array2`.
-type Array2Index = usize;
-
 #[derive(Debug, new, PartialEq, Eq)]
 struct TileInGrid {
     tile: Tile,
-    row: Array2Index,
-    col: Array2Index,
+    row: GridIndex,
+    col: GridIndex,
 }
 
 /// The positional relation between two tiles.
@@ -213,14 +202,8 @@ impl TileInGrid {
         direction: &Direction,
         tile_grid: &'a TileGrid,
     ) -> Option<&'a Self> {
-        let (new_row, new_col) = direction.translate(&self.row, &self.col);
-
-        // Don't return this tile if no tile in direction was found.
-        if new_row == self.row && new_col == self.col {
-            return None;
-        }
-
-        tile_grid.0.get((new_row, new_col))
+        let new_pos = direction.translate(&PositionInGrid::new(self.row, self.col))?;
+        tile_grid.0.get(&new_pos)
     }
 
     /// Given the pipe tile we previously stood on and the pipe tile we are
@@ -229,22 +212,37 @@ impl TileInGrid {
         &self,
         facing_direction: &Direction,
         tile_grid: &'a TileGrid,
-    ) -> (&'a Self, &Direction) {
-        // TODO: convert panics to proper error handling
+    ) -> anyhow::Result<(&'a Self, &Direction)> {
+        // Ensure we don't go back to the direction we came from.
+        let came_from_direction = facing_direction.invert();
 
         let Tile::Pipe(Pipe(a, b)) = &self.tile else {
-            panic!("method should never be called on a non-pipe tile");
+            bail!(
+                "the main loop is broken at row {}, column {}, which is not a pipe",
+                self.row + 1,
+                self.col + 1
+            );
         };
+        ensure!(
+            self.tile.points_in_direction(&came_from_direction),
+            "the main loop is broken at row {}, column {}, whose pipe does not connect back",
+            self.row + 1,
+            self.col + 1
+        );
 
-        // Ensure we don't go back to the direction we came from.
-        let came_from_direction = facing_direction.invert();
         let new_direction = if a == &came_from_direction { b } else { a };
 
         let new_tile = self
             .get_next_in_direction(new_direction, tile_grid)
-            .expect("main tile loop has ended unexpectedly, as no next tile was found");
-
-        (new_tile, new_direction)
+            .with_context(|| {
+                format!(
+                    "the main loop leaves the grid at row {}, column {}",
+                    self.row + 1,
+                    self.col + 1
+                )
+            })?;
+
+        Ok((new_tile, new_direction))
     }
 }
 
@@ -285,46 +283,6 @@ impl Tile {
 #[derive(Debug, PartialEq, Eq)]
 struct Pipe(Direction, Direction);
 
-#[derive(Debug, PartialEq, Eq, EnumIter)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    /// Invert the direction. Consider: if we were facing in this direction,
-    /// and turned around, in which direction would we be facing?
-    fn invert(&self) -> Self {
-        match self {
-            Self::North => Self::South,
-            Self::South => Self::North,
-            Self::East => Self::West,
-            Self::West => Self::East,
-        }
-    }
-
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes.
-    fn translate(&self, row: &Array2Index, col: &Array2Index) -> (Array2Index, Array2Index) {
-        match self {
-            Direction::North => (row.saturating_sub(1), *col),
-            Direction::South => (row + 1, *col),
-            Direction::East => (*row, col + 1),
-            Direction::West => (*row, col.saturating_sub(1)),
-        }
-    }
-}
-
-#[test]
-fn test_translate_direction() {
-    assert_eq!((0, 1), Direction::North.translate(&1, &1));
-    assert_eq!((2, 1), Direction::South.translate(&1, &1));
-    assert_eq!((1, 2), Direction::East.translate(&1, &1));
-    assert_eq!((1, 0), Direction::West.translate(&1, &1));
-}
-
 #[derive(Debug, FromStr)]
 enum TileParsed {
     #[display("|")]
@@ -357,23 +315,32 @@ fn round_up_div(dividend: usize, divisor: usize) -> usize {
     (dividend + 1) / divisor
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let tile_grid: TileGrid = puzzle_input.parse()?;
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<TileGrid> {
+    puzzle_input.parse()
+}
 
-    let main_loop_tiles = tile_grid.find_main_loop();
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(tile_grid: TileGrid) -> anyhow::Result<Solution> {
+    let main_loop_tiles = tile_grid.find_main_loop()?;
 
     let steps_till_point_farthest_from_starting_position = round_up_div(main_loop_tiles.len(), 2);
 
-    Ok(steps_till_point_farthest_from_starting_position.to_string())
+    Ok(steps_till_point_farthest_from_starting_position.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
-    /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    /// Provide multiple example details as `[(puzzle input, expected solution)]`.
+    pub fn example_details() -> impl Iterator<Item = (&'static str, Solution)> {
         let puzzle_input = indoc! {"
             ..F7.
             .FJ|.
@@ -388,6 +355,22 @@ pub mod example {
         // 14567
         // 23...
         let expected_solution = 8;
-        (puzzle_input, expected_solution.to_string())
+
+        // More than two pipes point towards `S`, which is an `F`, and the one
+        // west of it even leaves the grid.
+        let puzzle_input_2 = indoc! {"
+            .....
+            .|...
+            -S-7.
+            .|.|.
+            .L-J.
+        "};
+        let expected_solution_2 = 4;
+
+        [
+            (puzzle_input, expected_solution.into()),
+            (puzzle_input_2, expected_solution_2.into()),
+        ]
+        .into_iter()
     }
 }
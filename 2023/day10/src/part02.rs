@@ -1,175 +1,162 @@
-use anyhow::bail;
-use ndarray::Array2;
+use anyhow::{bail, ensure, Context};
+use aoc_grid::{Direction, Grid, GridIndex, PositionInGrid};
+use aoc_solution::Solution;
 use parse_display::FromStr;
-use std::{cmp::Ordering, str};
-use strum::{EnumIter, IntoEnumIterator};
+use std::{cmp::Ordering, collections::HashSet, str};
+use strum::IntoEnumIterator;
 
 #[derive(Debug)]
-struct TileGrid(Array2<TileInGrid>);
+pub struct TileGrid(Grid<TileInGrid>);
 
 impl str::FromStr for TileGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let rows = s.lines().count();
-        let cols = match s.lines().next() {
-            Some(first_row) => first_row.chars().count(),
-            None => bail!("Expected there to be at least one row, but found none."),
-        };
-
-        let grid_vec: Vec<_> = s
-            .lines()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.chars().enumerate().map(move |(col, c)| {
-                    // TODO: remove unwrap from parse
-                    let tile = Tile::from(c.to_string().as_str().parse::<TileParsed>().unwrap());
-                    TileInGrid::new(tile, row, col)
-                })
-            })
-            .collect();
-
-        let grid = Array2::from_shape_vec((rows, cols), grid_vec)?;
-        Ok(Self(grid).setup_starting_tile())
+        let grid = Grid::parse_with(s, |pos, c| {
+            let tile = Tile::from(c.to_string().as_str().parse::<TileParsed>()?);
+            Ok(TileInGrid::new(tile, pos.row, pos.col))
+        })?;
+        Self(grid).setup_starting_tile()
     }
 }
 
-// impl fmt::Display for TileGrid {
-//     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-//         for row in self.0.rows() {
-//             for tile in row {
-//                 write!(f, "{}", tile)?;
-//             }
-//         }
-//         todo!()
-//     }
-// }
-
 impl TileGrid {
     /// Replace the starting tile, which is initially `S`, with the pipe tile it
     /// represents in order to connect to its two neighboring pipes.
-    fn setup_starting_tile(mut self) -> Self {
-        let ((_, initial_dir_a), (_, initial_dir_b)) = self.find_initial_connected_pipes();
+    fn setup_starting_tile(mut self) -> anyhow::Result<Self> {
+        let (initial_dir_a, initial_dir_b) = self.find_starting_pipe()?;
 
         let starting_tile = self
             .0
             .iter_mut()
             .find(|tile| tile.is_starting_tile || matches!(tile.tile, Tile::AnimalStartingPosition))
-            .expect("there must be a starting tile");
+            .context("there is no starting tile")?;
 
         starting_tile.tile = Tile::Pipe(Pipe(initial_dir_a, initial_dir_b));
         starting_tile.is_starting_tile = true;
 
-        self
+        Ok(self)
     }
 
     /// Get the starting tile.
-    fn get_starting_tile(&self) -> &TileInGrid {
+    fn get_starting_tile(&self) -> anyhow::Result<&TileInGrid> {
         self.0
             .iter()
             .find(|tile| tile.is_starting_tile || matches!(tile.tile, Tile::AnimalStartingPosition))
-            .expect("there must be a starting tile")
+            .context("there is no starting tile")
     }
 
     /// Find the tiles that make up the main loop. The main loop is a set of
     /// connected pipes that form a loop from the starting tile back to the
     /// starting tile.
-    fn find_main_loop(&self) -> Vec<&TileInGrid> {
-        let starting_tile = self.get_starting_tile();
-
-        // TODO: we're calling find_initial_connected_pipes here again for a second time, unnecessary (but did it for borrow checking reasons)
-        let ((initial_pipe, initial_dir), (_, _)) = self.find_initial_connected_pipes();
-
-        // TODO: try using maybe fold for more idiomatic rust
-
-        let mut main_loop_tiles = vec![starting_tile];
+    fn find_main_loop(&self) -> anyhow::Result<Vec<&TileInGrid>> {
+        let starting_tile = self.get_starting_tile()?;
+        let Tile::Pipe(Pipe(initial_dir, _)) = starting_tile.tile else {
+            bail!("the starting tile has not been replaced by a pipe");
+        };
 
-        let mut cur_tile = initial_pipe;
-        let mut facing_dir = &initial_dir;
+        let (main_loop_tiles, _) = self.walk_loop(starting_tile, initial_dir)?;
 
-        while cur_tile != starting_tile {
-            main_loop_tiles.push(cur_tile);
-            (cur_tile, facing_dir) = cur_tile.find_next_connected_pipe(facing_dir, self);
-        }
-
-        main_loop_tiles
+        Ok(main_loop_tiles)
     }
 
-    /// Find one of the pipes connected to the starting tile. Return the
-    /// direction in which it was found, relative to the starting pipe, along
-    /// with the connected pipe tile itself.
-    fn find_initial_connected_pipes(&self) -> ((&TileInGrid, Direction), (&TileInGrid, Direction)) {
-        let starting_tile = self.get_starting_tile();
+    /// Find the two directions of the pipe the starting tile represents.
+    ///
+    /// More than two neighboring pipes may point towards the starting tile, so
+    /// walk along the pipes from each of them, and pick the first walk that
+    /// actually gets back to the starting tile. Its pipe connects the
+    /// direction the walk left in and the one it got back from.
+    fn find_starting_pipe(&self) -> anyhow::Result<(Direction, Direction)> {
+        let starting_tile = self.get_starting_tile()?;
 
         // Search every direction for connected pipes.
-        let mut initial_pipes: Vec<(&TileInGrid, Direction)> = Direction::iter()
-            .filter_map(|direction| {
-                if let Some(potential_pipe) = starting_tile.get_next_in_direction(&direction, self)
-                {
-                    if potential_pipe.is_connected_with_starting_tile(starting_tile) {
-                        return Some((potential_pipe, direction));
-                    }
-                }
-                None
+        let initial_dirs: Vec<Direction> = Direction::iter()
+            .filter(|direction| {
+                starting_tile
+                    .get_next_in_direction(direction, self)
+                    .is_some_and(|pipe| pipe.is_connected_with_starting_tile(starting_tile))
             })
             .collect();
+        ensure!(
+            initial_dirs.len() >= 2,
+            "expected at least two pipes to be connected to the starting tile"
+        );
+
+        let mut first_err = None;
+        for initial_dir in initial_dirs {
+            match self.walk_loop(starting_tile, initial_dir) {
+                Ok((_, returned_from_dir)) => return Ok((initial_dir, returned_from_dir)),
+                Err(err) => {
+                    first_err.get_or_insert(err);
+                }
+            }
+        }
 
-        // It doesn't matter which of the two initial pipes we choose to traverse.
-        let (Some((initial_pipe_a, initial_dir_a)), Some((initial_pipe_b, initial_dir_b))) =
-            (initial_pipes.pop(), initial_pipes.pop())
-        else {
-            panic!("expected there to be exactly two pipes connected to the starting tile");
-        };
-
-        (
-            (initial_pipe_a, initial_dir_a),
-            (initial_pipe_b, initial_dir_b),
-        )
+        Err(first_err.expect("there were pipes to walk along"))
     }
 
-    fn find_top_left_corner(&self) -> &TileInGrid {
-        let is_left_corner = |tile: &Tile| {
-            tile.points_in_direction(&Direction::South)
-                && tile.points_in_direction(&Direction::East)
-        };
-
-        let row_idx = 0;
-        let col_idx = 0;
+    /// Walk along the pipes, starting from the `starting_tile` in the
+    /// direction `initial_dir`, until getting back to the starting tile.
+    /// Return the tiles walked along, starting with the starting tile, and the
+    /// direction, relative to the starting tile, that the walk got back from.
+    fn walk_loop<'a>(
+        &'a self,
+        starting_tile: &'a TileInGrid,
+        initial_dir: Direction,
+    ) -> anyhow::Result<(Vec<&'a TileInGrid>, Direction)> {
+        let mut main_loop_tiles = vec![starting_tile];
 
-        loop {
-            if let Some(maybe_left_corner) = self.0.get((row_idx, col_idx)) {
-                if is_left_corner(&maybe_left_corner.tile) {
-                    //
-                    return maybe_left_corner;
-                }
+        let mut cur_tile = starting_tile
+            .get_next_in_direction(&initial_dir, self)
+            .context("the main loop leaves the grid at the starting tile")?;
+        let mut facing_dir = initial_dir;
 
-                //
-            }
+        while cur_tile != starting_tile {
+            main_loop_tiles.push(cur_tile);
+            let (next_tile, next_dir) = cur_tile.find_next_connected_pipe(&facing_dir, self)?;
+            (cur_tile, facing_dir) = (next_tile, *next_dir);
         }
-        todo!()
-    }
 
-    fn add_inside_direction_to_main_loop_tiles(&mut self) {
-        todo!()
+        Ok((main_loop_tiles, facing_dir.invert()))
     }
 
-    fn find_area_enclosed_by_main_loop(&self) -> usize {
-        todo!()
+    /// Count the tiles enclosed by the main loop. Scanning each row from left
+    /// to right, we are inside of the loop after crossing it an odd number of
+    /// times. Only main loop pipes pointing north count as crossings, so that
+    /// running along a horizontal stretch of the loop (e.g. `L--7` or `F--7`)
+    /// is counted correctly, and squeezing between pipes needs no special care.
+    fn find_area_enclosed_by_main_loop(&self) -> anyhow::Result<usize> {
+        let main_loop: HashSet<(GridIndex, GridIndex)> = self
+            .find_main_loop()?
+            .into_iter()
+            .map(|tile| (tile.row, tile.col))
+            .collect();
+
+        let enclosed_area = self
+            .0
+            .iter_rows()
+            .map(|row| {
+                row.fold((false, 0), |(inside, enclosed), tile| {
+                    if main_loop.contains(&(tile.row, tile.col)) {
+                        let crosses_loop = tile.tile.points_in_direction(&Direction::North);
+                        (inside ^ crosses_loop, enclosed)
+                    } else {
+                        (inside, enclosed + usize::from(inside))
+                    }
+                })
+                .1
+            })
+            .sum();
+
+        Ok(enclosed_area)
     }
 }
 
-/// An index into a `ndarray::Array2`.
-type Array2Index = usize;
-
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct TileInGrid {
     tile: Tile,
     is_starting_tile: bool,
-    // TODO: maybe fix with type system to make clearer
-    /// If we are standing on this tile, in which direction is the inside of
-    /// the area enclosed by the main loop?
-    inside_enclosed_direction: Option<Direction>,
-    row: Array2Index,
-    col: Array2Index,
+    row: GridIndex,
+    col: GridIndex,
 }
 
 /// The positional relation between two tiles.
@@ -183,12 +170,10 @@ enum PositionRelation {
 }
 
 impl TileInGrid {
-    fn new(tile: Tile, row: Array2Index, col: Array2Index) -> Self {
+    fn new(tile: Tile, row: GridIndex, col: GridIndex) -> Self {
         Self {
             tile,
             is_starting_tile: false,
-            // We choose any random direction here, this field should only be used once
-            inside_enclosed_direction: None,
             row,
             col,
         }
@@ -289,14 +274,8 @@ impl TileInGrid {
         direction: &Direction,
         tile_grid: &'a TileGrid,
     ) -> Option<&'a Self> {
-        let (new_row, new_col) = direction.translate(&self.row, &self.col);
-
-        // Don't return this tile if no tile in direction was found.
-        if new_row == self.row && new_col == self.col {
-            return None;
-        }
-
-        tile_grid.0.get((new_row, new_col))
+        let new_pos = direction.translate(&PositionInGrid::new(self.row, self.col))?;
+        tile_grid.0.get(&new_pos)
     }
 
     /// Given the pipe tile we previously stood on and the pipe tile we are
@@ -305,22 +284,37 @@ impl TileInGrid {
         &self,
         facing_direction: &Direction,
         tile_grid: &'a TileGrid,
-    ) -> (&'a Self, &Direction) {
-        // TODO: convert panics to proper error handling
+    ) -> anyhow::Result<(&'a Self, &Direction)> {
+        // Ensure we don't go back to the direction we came from.
+        let came_from_direction = facing_direction.invert();
 
         let Tile::Pipe(Pipe(a, b)) = &self.tile else {
-            panic!("method should never be called on a non-pipe tile");
+            bail!(
+                "the main loop is broken at row {}, column {}, which is not a pipe",
+                self.row + 1,
+                self.col + 1
+            );
         };
+        ensure!(
+            self.tile.points_in_direction(&came_from_direction),
+            "the main loop is broken at row {}, column {}, whose pipe does not connect back",
+            self.row + 1,
+            self.col + 1
+        );
 
-        // Ensure we don't go back to the direction we came from.
-        let came_from_direction = facing_direction.invert();
         let new_direction = if a == &came_from_direction { b } else { a };
 
         let new_tile = self
             .get_next_in_direction(new_direction, tile_grid)
-            .expect("main tile loop has ended unexpectedly, as no next tile was found");
-
-        (new_tile, new_direction)
+            .with_context(|| {
+                format!(
+                    "the main loop leaves the grid at row {}, column {}",
+                    self.row + 1,
+                    self.col + 1
+                )
+            })?;
+
+        Ok((new_tile, new_direction))
     }
 }
 
@@ -361,46 +355,6 @@ impl Tile {
 #[derive(Debug, PartialEq, Eq, Clone)]
 struct Pipe(Direction, Direction);
 
-#[derive(Debug, PartialEq, Eq, EnumIter, Clone)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    /// Invert the direction. Consider: if we were facing in this direction,
-    /// and turned around, in which direction would we be facing?
-    fn invert(&self) -> Self {
-        match self {
-            Self::North => Self::South,
-            Self::South => Self::North,
-            Self::East => Self::West,
-            Self::West => Self::East,
-        }
-    }
-
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes.
-    fn translate(&self, row: &Array2Index, col: &Array2Index) -> (Array2Index, Array2Index) {
-        match self {
-            Direction::North => (row.saturating_sub(1), *col),
-            Direction::South => (row + 1, *col),
-            Direction::East => (*row, col + 1),
-            Direction::West => (*row, col.saturating_sub(1)),
-        }
-    }
-}
-
-#[test]
-fn test_translate_direction() {
-    assert_eq!((0, 1), Direction::North.translate(&1, &1));
-    assert_eq!((2, 1), Direction::South.translate(&1, &1));
-    assert_eq!((1, 2), Direction::East.translate(&1, &1));
-    assert_eq!((1, 0), Direction::West.translate(&1, &1));
-}
-
 #[derive(Debug, FromStr)]
 enum TileParsed {
     #[display("|")]
@@ -428,31 +382,59 @@ enum TileParsed {
     AnimalStartingPosition,
 }
 
-/// Perform a division, but round up if the quotient is not a whole number.
-fn round_up_div(dividend: usize, divisor: usize) -> usize {
-    (dividend + 1) / divisor
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<TileGrid> {
+    puzzle_input.parse()
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let tile_grid: TileGrid = puzzle_input.parse()?;
-
-    dbg!(&tile_grid);
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(tile_grid: TileGrid) -> anyhow::Result<Solution> {
+    let enclosed_area = tile_grid.find_area_enclosed_by_main_loop()?;
 
-    let main_loop_tiles = tile_grid.find_main_loop();
-
-    let steps_till_point_farthest_from_starting_position = round_up_div(main_loop_tiles.len(), 2);
+    Ok(enclosed_area.into())
+}
 
-    Ok(steps_till_point_farthest_from_starting_position.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
-    /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
-        let puzzle_input = indoc! {"
+    /// Provide multiple example details as `[(puzzle input, expected solution)]`.
+    pub fn example_details() -> impl Iterator<Item = (&'static str, Solution)> {
+        let puzzle_input_1 = indoc! {"
+            ...........
+            .S-------7.
+            .|F-----7|.
+            .||.....||.
+            .||.....||.
+            .|L-7.F-J|.
+            .|..|.|..|.
+            .L--J.L--J.
+            ...........
+        "};
+        let expected_solution_1 = 4;
+
+        // The tiles between the two inner `||` are outside of the main loop,
+        // even though there is no gap between the pipes to squeeze through.
+        let puzzle_input_2 = indoc! {"
+            ..........
+            .S------7.
+            .|F----7|.
+            .||....||.
+            .||....||.
+            .|L-7F-J|.
+            .|..||..|.
+            .L--JL--J.
+            ..........
+        "};
+        let expected_solution_2 = 4;
+
+        let puzzle_input_3 = indoc! {"
             .F----7F7F7F7F-7....
             .|F--7||||||||FJ....
             .||.FJ||||||||L7....
@@ -465,7 +447,7 @@ pub mod example {
             ....L---J.LJ.LJLJ...
         "};
 
-        // Tiles enclosed by main loop (I) and tiles outisde of main loop (0):
+        // Tiles enclosed by main loop (I) and tiles outside of main loop (O):
         // OF----7F7F7F7F-7OOOO
         // O|F--7||||||||FJOOOO
         // O||OFJ||||||||L7OOOO
@@ -477,7 +459,52 @@ pub mod example {
         // OOOOFJL-7O||O||||OOO
         // OOOOL---JOLJOLJLJOOO
 
-        let expected_solution = 8;
-        (puzzle_input, expected_solution.to_string())
+        let expected_solution_3 = 8;
+
+        // Pipes that are not part of the main loop count as enclosed tiles,
+        // too.
+        let puzzle_input_4 = indoc! {"
+            FF7FSF7F7F7F7F7F---7
+            L|LJ||||||||||||F--J
+            FL-7LJLJ||||||LJL-77
+            F--JF--7||LJLJ7F7FJ-
+            L---JF-JLJ.||-FJLJJ7
+            |F|F-JF---7F7-L7L|7|
+            |FFJF7L7F-JF7|JL---7
+            7-L-JL7||F7|L7F-7F7|
+            L.L7LFJ|||||FJL7||LJ
+            L7JLJL-JLJLJL--JLJ.L
+        "};
+        let expected_solution_4 = 10;
+
+        // More than two pipes point towards `S`, which is a `J`, and the one
+        // west of it even leaves the grid.
+        let puzzle_input_5 = indoc! {"
+            ..|..
+            .F-7.
+            -|.|.
+            .L-S-
+            ...|.
+        "};
+        let expected_solution_5 = 1;
+
+        let puzzle_input_6 = indoc! {"
+            .....
+            .F7..
+            -S|..
+            .LJ..
+            .....
+        "};
+        let expected_solution_6 = 0;
+
+        [
+            (puzzle_input_1, expected_solution_1.into()),
+            (puzzle_input_2, expected_solution_2.into()),
+            (puzzle_input_3, expected_solution_3.into()),
+            (puzzle_input_4, expected_solution_4.into()),
+            (puzzle_input_5, expected_solution_5.into()),
+            (puzzle_input_6, expected_solution_6.into()),
+        ]
+        .into_iter()
     }
 }
@@ -1,33 +1,19 @@
+use aoc_grid::{Grid, GridIndex};
+use aoc_solution::Solution;
 use derive_new::new;
-use grid::Grid;
 use itertools::Itertools;
 use parse_display::{Display, FromStr};
-use std::{fmt, str};
+use std::str;
 use strum::EnumIs;
 
-#[derive(Debug)]
-struct SpaceGrid(Grid<SpaceObjectInGrid>);
+#[derive(Debug, Display)]
+#[display("{0}")]
+pub struct SpaceGrid(Grid<SpaceObjectInGrid>);
 
 impl str::FromStr for SpaceGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cols = s
-            .lines()
-            .next()
-            .map(|first_row| first_row.chars().count())
-            .unwrap_or(0);
-
-        let grid_vec = s
-            .lines()
-            .flat_map(|line| {
-                line.chars().map(|c| {
-                    // TODO: remove unwrap from parse
-                    c.to_string().as_str().parse::<SpaceObject>().unwrap()
-                })
-            })
-            .collect_vec();
-
-        let mut grid = Grid::from_vec(grid_vec, cols);
+        let mut grid: Grid<SpaceObject> = s.parse()?;
 
         // TODO: remove code duplication between duplicating empty rows and columns.
 
@@ -64,38 +50,17 @@ impl str::FromStr for SpaceGrid {
         }
 
         // Add an internally saved index to each grid element.
-        let cols = grid.cols();
-        let grid_vec_with_indexes = grid
-            .into_vec()
-            .into_iter()
-            .enumerate()
-            .map(|(idx, space_object)| {
-                let row_idx = idx / cols;
-                let col_idx = idx % cols;
-                SpaceObjectInGrid::new(space_object, row_idx, col_idx)
-            })
-            .collect_vec();
-        let grid = Grid::from_vec(grid_vec_with_indexes, cols);
+        let grid = grid.map_with_position(|pos, space_object| {
+            SpaceObjectInGrid::new(space_object, pos.row, pos.col)
+        });
 
         Ok(Self(grid))
     }
 }
 
-impl fmt::Display for SpaceGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in self.0.iter_rows() {
-            for space_object in row {
-                write!(f, "{}", space_object)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
-    }
-}
-
 impl SpaceGrid {
     /// Find all pairs of galaxies, and only count each pair once.
-    fn find_all_galaxy_pairs(&self) -> impl Iterator<Item = GalaxyPair> {
+    fn find_all_galaxy_pairs(&self) -> impl Iterator<Item = GalaxyPair<'_>> {
         self.0
             .iter()
             .filter(|space_object| space_object.space_object.is_galaxy())
@@ -117,16 +82,13 @@ impl<'a> GalaxyPair<'a> {
     }
 }
 
-/// An index into a `ndarray::Array2`.
-type Array2Index = usize;
-
 /// A `SpaceObject`, but including row and column indexes in the `SpaceGrid`.
 #[derive(Debug, Display, new, PartialEq, Eq)]
 #[display("{space_object}")]
 struct SpaceObjectInGrid {
     space_object: SpaceObject,
-    row: Array2Index,
-    col: Array2Index,
+    row: GridIndex,
+    col: GridIndex,
 }
 
 #[derive(Debug, FromStr, Display, Clone, PartialEq, Eq, EnumIs)]
@@ -138,25 +100,33 @@ enum SpaceObject {
     EmptySpace,
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    // TODO: remove unwrap
-    let space_grid: SpaceGrid = puzzle_input.parse().unwrap();
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<SpaceGrid> {
+    puzzle_input.parse()
+}
 
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(space_grid: SpaceGrid) -> anyhow::Result<Solution> {
     let shortest_path_lengths_sum: usize = space_grid
         .find_all_galaxy_pairs()
         .map(GalaxyPair::shortest_path_len)
         .sum();
 
-    Ok(shortest_path_lengths_sum.to_string())
+    Ok(shortest_path_lengths_sum.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             ...#......
             .......#..
@@ -170,6 +140,6 @@ pub mod example {
             #...#.....
         "};
         let expected_solution = 374;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
@@ -1,141 +1,171 @@
-use derive_new::new;
-use grid::Grid;
+use anyhow::{ensure, Context};
+use aoc_grid::{Grid, GridIndex};
+use aoc_solution::Solution;
 use itertools::Itertools;
 use parse_display::{Display, FromStr};
-use std::{fmt, str};
+use std::{cmp::Reverse, str};
 use strum::EnumIs;
 
-#[cfg(not(test))]
-const SPACE_EXPANSION_FACTOR: usize = 1_000_000;
+/// The factor by which each row or column of completely empty space grows in
+/// the puzzle.
+pub const EXPANSION_FACTOR: usize = 1_000_000;
 
-#[derive(Debug)]
-struct SpaceGrid(Grid<SpaceObjectInGrid>);
+/// The image of the universe, as observed before accounting for the cosmic
+/// expansion.
+#[derive(Debug, Display)]
+#[display("{0}")]
+pub struct SpaceGrid(Grid<SpaceObject>);
 
 impl str::FromStr for SpaceGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cols = s
-            .lines()
-            .next()
-            .map(|first_row| first_row.chars().count())
-            .unwrap_or(0);
-
-        let grid_vec = s
-            .lines()
+        Ok(Self(s.parse()?))
+    }
+}
+
+impl SpaceGrid {
+    /// Account for the cosmic expansion, where each row or column of
+    /// completely empty space is replaced by `expansion_factor` of them. The
+    /// grid itself is left untouched: The expanded position of each galaxy is
+    /// computed from the number of empty rows and columns before it.
+    ///
+    /// The `expansion_factor` must be at least 1, since an empty row or
+    /// column cannot be replaced by none of them.
+    pub fn expand(&self, expansion_factor: usize) -> anyhow::Result<ExpandedUniverse> {
+        ensure!(
+            expansion_factor >= 1,
+            "the expansion factor must be at least 1, but is {}",
+            expansion_factor
+        );
+        let growth = expansion_factor - 1;
+        let expanded = |index: GridIndex, empty_before: usize| {
+            empty_before
+                .checked_mul(growth)
+                .and_then(|offset| index.checked_add(offset))
+                .context("the expanded universe is too large")
+        };
+
+        let empty_rows_before = empty_lines_before(
+            self.0
+                .iter_rows()
+                .map(|mut row| row.all(SpaceObject::is_empty_space)),
+        );
+        let empty_cols_before = empty_lines_before(
+            self.0
+                .iter_cols()
+                .map(|mut col| col.all(SpaceObject::is_empty_space)),
+        );
+
+        // Every empty row or column before a galaxy pushes it further away by
+        // the number of rows or columns it was expanded by.
+        let galaxies = self
+            .0
+            .indexed_iter()
+            .filter(|(_, space_object)| space_object.is_galaxy())
             .enumerate()
-            .flat_map(|(row_idx, line)| {
-                line.chars().enumerate().map(move |(col_idx, c)| {
-                    // TODO: remove unwrap from parse
-                    let space_object = c.to_string().as_str().parse::<SpaceObject>().unwrap();
-                    SpaceObjectInGrid::new(space_object, row_idx, col_idx)
+            .map(|(idx, (pos, _))| {
+                Ok(Galaxy {
+                    number: idx + 1,
+                    row: expanded(pos.row, empty_rows_before[pos.row])?,
+                    col: expanded(pos.col, empty_cols_before[pos.col])?,
                 })
             })
-            .collect_vec();
-
-        let grid = Grid::from_vec(grid_vec, cols);
+            .collect::<anyhow::Result<_>>()?;
 
-        Ok(Self(grid).account_for_cosmic_expansion())
+        Ok(ExpandedUniverse { galaxies })
     }
 }
 
-impl fmt::Display for SpaceGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for row in self.0.iter_rows() {
-            for space_object in row {
-                write!(f, "{}", space_object)?;
-            }
-            writeln!(f)?;
-        }
-        Ok(())
+/// For each row (or column), given whether it is empty, count the empty rows
+/// (or columns) before it.
+fn empty_lines_before(is_empty: impl Iterator<Item = bool>) -> Vec<usize> {
+    is_empty
+        .scan(0, |empty_so_far, is_empty| {
+            let empty_before = *empty_so_far;
+            *empty_so_far += usize::from(is_empty);
+            Some(empty_before)
+        })
+        .collect()
+}
+
+/// Galaxies are numbered from 1 in the order they appear in the image, row by
+/// row.
+pub type GalaxyNumber = usize;
+
+/// A galaxy at its position after the cosmic expansion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Galaxy {
+    pub number: GalaxyNumber,
+    pub row: GridIndex,
+    pub col: GridIndex,
+}
+
+impl Galaxy {
+    /// Find the length of the shortest path to another galaxy.
+    pub fn distance(&self, other: &Galaxy) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
     }
 }
 
-impl SpaceGrid {
-    /// Account for the cosmic expansion, where each row or column of completely
-    /// empty space is expanded by the `SPACE_EXPANSION_FACTOR`. For performance
-    /// reasons, we do not actually insert all of these new rows or columns, but
-    /// only adjust the internal row and column indexes of the galaxies.
-    fn account_for_cosmic_expansion(mut self) -> Self {
-        // TODO: remove code duplication between finding empty rows and columns.
-
-        let empty_row_indexes = self
-            .0
-            .iter_rows()
-            .enumerate()
-            // Only keep empty rows.
-            .filter(|(_, row)| row.clone().all(|o| o.space_object.is_empty_space()))
-            .map(|(row_idx, _)| row_idx)
-            .collect_vec();
+/// Two different galaxies and the length of the shortest path between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GalaxyPair {
+    pub a: GalaxyNumber,
+    pub b: GalaxyNumber,
+    pub distance: usize,
+}
 
-        let empty_col_indexes = self
-            .0
-            .iter_cols()
-            .enumerate()
-            // Only keep empty columns.
-            .filter(|(_, col)| col.clone().all(|o| o.space_object.is_empty_space()))
-            .map(|(col_idx, _)| col_idx)
-            .collect_vec();
-
-        // Adjust the row and column indexes of each galaxy to reflect the
-        // space expansion.
-        self.0
-            .iter_mut()
-            .filter(|o| o.space_object.is_galaxy())
-            .for_each(|space_object| {
-                // TODO: remove code duplication
-
-                let row_factor = empty_row_indexes
-                    .iter()
-                    .filter(|&&idx| idx < space_object.row)
-                    .count();
-                let new_row = space_object.row + row_factor * (SPACE_EXPANSION_FACTOR - 1);
-
-                let col_factor = empty_col_indexes
-                    .iter()
-                    .filter(|&&idx| idx < space_object.col)
-                    .count();
-                let new_col = space_object.col + col_factor * (SPACE_EXPANSION_FACTOR - 1);
-
-                space_object.row = new_row;
-                space_object.col = new_col;
-            });
-
-        self
+/// All galaxies of a `SpaceGrid` after the cosmic expansion.
+#[derive(Debug)]
+pub struct ExpandedUniverse {
+    /// Ordered by galaxy number.
+    galaxies: Vec<Galaxy>,
+}
+
+impl ExpandedUniverse {
+    pub fn galaxies(&self) -> &[Galaxy] {
+        &self.galaxies
+    }
+
+    pub fn galaxy(&self, number: GalaxyNumber) -> Option<&Galaxy> {
+        self.galaxies.get(number.checked_sub(1)?)
+    }
+
+    /// Find the length of the shortest path between the galaxies with the
+    /// numbers `a` and `b`, if both exist.
+    pub fn distance(&self, a: GalaxyNumber, b: GalaxyNumber) -> Option<usize> {
+        Some(self.galaxy(a)?.distance(self.galaxy(b)?))
     }
 
     /// Find all pairs of galaxies, and only count each pair once.
-    fn find_all_galaxy_pairs(&self) -> impl Iterator<Item = GalaxyPair> {
-        self.0
+    pub fn pairs(&self) -> impl Iterator<Item = GalaxyPair> + '_ {
+        self.galaxies
             .iter()
-            .filter(|space_object| space_object.space_object.is_galaxy())
             .tuple_combinations()
-            .map(|(a, b)| GalaxyPair(a, b))
+            .map(|(a, b)| GalaxyPair {
+                a: a.number,
+                b: b.number,
+                distance: a.distance(b),
+            })
     }
-}
-
-struct GalaxyPair<'a>(&'a SpaceObjectInGrid, &'a SpaceObjectInGrid);
-
-impl<'a> GalaxyPair<'a> {
-    /// Find the length of the shortest path between two galaxies.
-    fn shortest_path_len(self) -> usize {
-        let (a, b) = (self.0, self.1);
 
-        let row_delta = a.row.abs_diff(b.row);
-        let col_delta = a.col.abs_diff(b.col);
-        row_delta + col_delta
+    /// Find the pair of galaxies with the shortest path between them, or
+    /// `None` if there are less than two galaxies. Ties are broken by the
+    /// galaxy numbers.
+    pub fn closest_pair(&self) -> Option<GalaxyPair> {
+        self.pairs().min_by_key(|pair| pair.distance)
     }
-}
 
-/// An index into a `ndarray::Array2`.
-type Array2Index = usize;
+    /// Find the pair of galaxies with the longest shortest path between them,
+    /// or `None` if there are less than two galaxies. Ties are broken by the
+    /// galaxy numbers.
+    pub fn farthest_pair(&self) -> Option<GalaxyPair> {
+        self.pairs().min_by_key(|pair| Reverse(pair.distance))
+    }
 
-/// A `SpaceObject`, but including row and column indexes in the `SpaceGrid`.
-#[derive(Debug, Display, new, PartialEq, Eq)]
-#[display("{space_object}")]
-struct SpaceObjectInGrid {
-    space_object: SpaceObject,
-    row: Array2Index,
-    col: Array2Index,
+    pub fn sum_of_distances(&self) -> usize {
+        self.pairs().map(|pair| pair.distance).sum()
+    }
 }
 
 #[derive(Debug, FromStr, Display, Clone, PartialEq, Eq, EnumIs)]
@@ -147,28 +177,31 @@ enum SpaceObject {
     EmptySpace,
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    // TODO: remove unwrap
-    let space_grid: SpaceGrid = puzzle_input.parse().unwrap();
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<SpaceGrid> {
+    puzzle_input.parse()
+}
 
-    let shortest_path_lengths_sum: usize = space_grid
-        .find_all_galaxy_pairs()
-        .map(GalaxyPair::shortest_path_len)
-        .sum();
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(space_grid: SpaceGrid, expansion_factor: usize) -> anyhow::Result<Solution> {
+    let shortest_path_lengths_sum = space_grid.expand(expansion_factor)?.sum_of_distances();
 
-    Ok(shortest_path_lengths_sum.to_string())
+    Ok(shortest_path_lengths_sum.into())
 }
 
-#[cfg(test)]
-const SPACE_EXPANSION_FACTOR: usize = 10;
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str, expansion_factor: usize) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?, expansion_factor)
+}
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
-    /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    /// Provide the example details as `(puzzle input, expected solution)`,
+    /// where the solution is for an expansion factor of 10.
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             ...#......
             .......#..
@@ -182,6 +215,44 @@ pub mod example {
             #...#.....
         "};
         let expected_solution = 1030;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
+
+#[test]
+fn test_galaxy_queries() -> anyhow::Result<()> {
+    let (puzzle_input, _) = example::example_details();
+    let universe = parse(puzzle_input)?.expand(2)?;
+
+    assert_eq!(Some(9), universe.distance(5, 9));
+    assert_eq!(Some(15), universe.distance(1, 7));
+    assert_eq!(Some(17), universe.distance(3, 6));
+    assert_eq!(Some(5), universe.distance(8, 9));
+    assert_eq!(Some(0), universe.distance(4, 4));
+    assert_eq!(None, universe.distance(0, 1));
+    assert_eq!(None, universe.distance(1, 10));
+
+    assert_eq!(
+        Some(GalaxyPair {
+            a: 2,
+            b: 4,
+            distance: 5
+        }),
+        universe.closest_pair()
+    );
+    assert_eq!(
+        Some(GalaxyPair {
+            a: 2,
+            b: 8,
+            distance: 19
+        }),
+        universe.farthest_pair()
+    );
+
+    assert_eq!(8410, parse(puzzle_input)?.expand(100)?.sum_of_distances());
+
+    assert!(parse(puzzle_input)?.expand(0).is_err());
+    assert!(parse(puzzle_input)?.expand(usize::MAX).is_err());
+
+    Ok(())
+}
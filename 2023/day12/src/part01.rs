@@ -1,4 +1,5 @@
 use anyhow::Result;
+use aoc_solution::Solution;
 use derive_more::From;
 use derive_new::new;
 use itertools::Itertools;
@@ -8,7 +9,7 @@ use strum::EnumIs;
 
 #[derive(Debug, FromStr, new)]
 #[display("{springs} {damaged_spring_groups}")]
-struct Line {
+pub struct Line {
     springs: Springs,
     damaged_spring_groups: DamagedSpringGroups,
 }
@@ -171,11 +172,16 @@ enum Spring {
     Unknown,
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let sum: usize = puzzle_input
-        .lines()
-        .map(|line| line.parse::<Line>().unwrap())
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<Line>> {
+    let lines = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(lines)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(lines: Vec<Line>) -> anyhow::Result<Solution> {
+    let sum: usize = lines
+        .into_iter()
         .flat_map(|line| {
             line.springs
                 .all_possible_configurations()
@@ -186,15 +192,21 @@ pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
         })
         .count();
 
-    Ok(sum.to_string())
+    Ok(sum.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             ???.### 1,1,3
             .??..??...?##. 1,1,3
@@ -204,6 +216,6 @@ pub mod example {
             ?###???????? 3,2,1
         "};
         let expected_solution = 21;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
@@ -1,123 +1,145 @@
 use anyhow::Result;
+use aoc_solution::Solution;
 use derive_more::From;
 use derive_new::new;
-use indicatif::ParallelProgressIterator;
-use itertools::{izip, Itertools, Position};
-use memoize::memoize;
+use itertools::{Itertools, Position};
 use parse_display::{Display, FromStr};
-use rayon::prelude::*;
-use std::{cell::RefCell, collections::HashMap, fmt, iter, rc::Rc, str};
+use std::{collections::HashMap, fmt, iter, str};
 use strum::EnumIs;
 
-/// The factor with which to expand/unfold each `Spring`.
-// const UNFOLDING_FACTOR: usize = 5;
-const UNFOLDING_FACTOR: usize = 2;
+/// The factor with which each `Line` is expanded/unfolded in the puzzle.
+pub const UNFOLDING_FACTOR: usize = 5;
 
 #[derive(Debug, FromStr, Display, new, Hash, Clone, PartialEq, Eq)]
 #[display("{springs} {damaged_spring_groups}")]
-struct Line {
+pub struct Line {
     springs: Springs,
     damaged_spring_groups: DamagedSpringGroups,
 }
 
-#[memoize]
-fn all_valid_configurations_builder(line: Line, idx: usize, mut cur_config: Springs) -> usize {
-    let recurse = |next_spring, mut new_cur_config: Springs| {
-        new_cur_config.0.push(next_spring);
-        // TODO: inefficient clone
-        all_valid_configurations_builder(line.clone(), idx + 1, new_cur_config)
-    };
+/// The state of a partially built configuration of springs, used as the key
+/// in the dynamic programming table of `Line::all_valid_configurations`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct PartialConfiguration {
+    /// The index of the `DamagedSpringGroup` that is currently being built,
+    /// or the next one if no group is being built right now.
+    group_idx: usize,
+    /// The length of the run of damaged springs at the end of the
+    /// configuration so far.
+    run_len: usize,
+}
 
-    // println!("{}", &cur_config);
+impl Line {
+    /// Unfold the springs and damaged spring groups: Both are repeated
+    /// `unfolding_factor` times, where the repetitions of the springs are
+    /// separated by a `Spring::Unknown`.
+    fn unfold(self, unfolding_factor: usize) -> Self {
+        let repeated_springs = iter::repeat_n(self.springs.0, unfolding_factor);
+        let springs = Itertools::intersperse(repeated_springs, vec![Spring::Unknown])
+            .flatten()
+            .collect_vec();
 
-    // Early exit if the start of the built-up configuration doesn't
-    // match the grouping pattern.
-    if !cur_config.start_is_valid(&line.damaged_spring_groups) {
-        return 0;
-    }
+        let groups = iter::repeat_n(self.damaged_spring_groups.0, unfolding_factor)
+            .flatten()
+            .collect_vec();
 
-    match line.springs.0.get(idx) {
-        Some(next_spring) => match next_spring {
-            Spring::Unknown => {
-                // Recurse on all possibilities.
-                recurse(Spring::Damaged, cur_config.clone())
-                    + recurse(Spring::Operational, cur_config)
-            }
-            known => recurse(known.clone(), cur_config),
-        },
-        None => {
-            if cur_config.is_valid(&line.damaged_spring_groups) {
-                // println!("found valid config: {}", cur_config);
-                1
-            } else {
-                0
-            }
-        }
+        Self::new(springs.into(), groups.into())
     }
-}
-
-// Spring => [(is_damaged, group_len)]
-type GroupCacheType = HashMap<Springs, Vec<(bool, usize)>>;
-type GroupCache = Rc<RefCell<GroupCacheType>>;
 
-impl Line {
     /// Get number of valid configurations of these springs. This means every
     /// `Spring::Unknown` is replaced with the other possibilities, and all
     /// combinations of this replacing are captured. A valid configuration is
     /// one where the `Springs` match the `DamagedSpringGroups`.
-    fn all_valid_configurations(self) -> usize {
-        // println!("getting all valid configs for: {}", self);
-
-        let mut group_cache: GroupCache = Rc::new(RefCell::new(HashMap::new()));
-
-        // let num_valid_configs =
-        //     self.all_valid_configurations_builder(0, Springs::default(), group_cache);
+    ///
+    /// Springs are processed one at a time, while keeping track of how many
+    /// configurations lead to each `PartialConfiguration`. Configurations that
+    /// already contradict the `DamagedSpringGroups` are dropped right away.
+    fn all_valid_configurations(&self) -> usize {
+        let groups = &self.damaged_spring_groups.0;
+        let group_len = |group_idx: usize| groups.get(group_idx).map(|group| group.0);
+
+        let start = PartialConfiguration {
+            group_idx: 0,
+            run_len: 0,
+        };
+        let mut configs: HashMap<PartialConfiguration, usize> = HashMap::from([(start, 1)]);
+
+        for spring in self.springs.0.iter() {
+            let mut next_configs = HashMap::new();
+            let mut add = |config, count| *next_configs.entry(config).or_default() += count;
+
+            for (config, count) in configs {
+                let PartialConfiguration { group_idx, run_len } = config;
+
+                if !spring.is_operational() {
+                    // Extend the run of damaged springs, if the current group
+                    // has room left.
+                    if group_len(group_idx).is_some_and(|len| run_len < len) {
+                        let run_len = run_len + 1;
+                        add(PartialConfiguration { group_idx, run_len }, count);
+                    }
+                }
 
-        let num_valid_configs = all_valid_configurations_builder(self, 0, Springs::default());
+                if !spring.is_damaged() {
+                    // End the run of damaged springs, if there is one and it
+                    // completes the current group.
+                    if run_len == 0 {
+                        add(config, count);
+                    } else if group_len(group_idx) == Some(run_len) {
+                        let next_group = PartialConfiguration {
+                            group_idx: group_idx + 1,
+                            run_len: 0,
+                        };
+                        add(next_group, count);
+                    }
+                }
+            }
 
-        // println!("valid configs len: {}", valid_configs.len());
+            configs = next_configs;
+        }
 
-        num_valid_configs
+        configs
+            .into_iter()
+            .filter(|(config, _)| {
+                let all_groups_completed = config.group_idx == groups.len() && config.run_len == 0;
+                let last_group_completed = config.group_idx + 1 == groups.len()
+                    && group_len(config.group_idx) == Some(config.run_len);
+                all_groups_completed || last_group_completed
+            })
+            .map(|(_, count)| count)
+            .sum()
     }
+}
 
-    /// A recursive helper for `all_valid_configurations`.
-    fn all_valid_configurations_builder(
-        &self,
-        idx: usize,
-        mut cur_config: Springs,
-        group_cache: GroupCache,
-    ) -> usize {
-        let recurse = |next_spring, mut new_cur_config: Springs| {
-            new_cur_config.0.push(next_spring);
-            self.all_valid_configurations_builder(idx + 1, new_cur_config, group_cache.clone())
-        };
-
-        // println!("{}", &cur_config);
+#[test]
+fn test_all_valid_configurations() {
+    /// Count the valid configurations by trying every possible replacement
+    /// of each `Spring::Unknown`.
+    fn brute_force(line: &Line) -> usize {
+        line.springs
+            .0
+            .iter()
+            .map(|spring| match spring {
+                Spring::Unknown => vec![Spring::Operational, Spring::Damaged],
+                known => vec![known.clone()],
+            })
+            .multi_cartesian_product()
+            .filter(|springs| Springs(springs.clone()).is_valid(&line.damaged_spring_groups))
+            .count()
+    }
 
-        // Early exit if the start of the built-up configuration doesn't
-        // match the grouping pattern.
-        if !cur_config.start_is_valid(&self.damaged_spring_groups) {
-            return 0;
-        }
+    for line in [
+        "???.### 1,1,3",
+        "?###???????? 3,2,1",
+        "?#?#?#?#?#?#?#? 1,3,1,6",
+    ] {
+        let line: Line = line.parse().unwrap();
+        assert_eq!(brute_force(&line), line.all_valid_configurations());
+    }
 
-        match self.springs.0.get(idx) {
-            Some(next_spring) => match next_spring {
-                Spring::Unknown => {
-                    // Recurse on all possibilities.
-                    recurse(Spring::Damaged, cur_config.clone())
-                        + recurse(Spring::Operational, cur_config)
-                }
-                known => recurse(known.clone(), cur_config),
-            },
-            None => {
-                if cur_config.is_valid(&self.damaged_spring_groups) {
-                    // println!("found valid config: {}", cur_config);
-                    1
-                } else {
-                    0
-                }
-            }
-        }
+    for line in ["???.### 1,1,3", ".??..??...?##. 1,1,3"] {
+        let line: Line = line.parse::<Line>().unwrap().unfold(2);
+        assert_eq!(brute_force(&line), line.all_valid_configurations());
     }
 }
 
@@ -127,16 +149,11 @@ struct DamagedSpringGroups(Vec<DamagedSpringGroup>);
 impl str::FromStr for DamagedSpringGroups {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let folded_groups: Vec<_> = s
+        let groups: Vec<_> = s
             .split(',')
             .map(|c| c.parse::<DamagedSpringGroup>())
             .collect::<Result<_, _>>()?;
 
-        let groups = iter::repeat(folded_groups)
-            .take(UNFOLDING_FACTOR)
-            .flatten()
-            .collect_vec();
-
         Ok(Self(groups))
     }
 }
@@ -162,17 +179,11 @@ struct Springs(Vec<Spring>);
 impl str::FromStr for Springs {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let folded_springs: Vec<_> = s
+        let springs: Vec<_> = s
             .chars()
             .map(|c| c.to_string().as_str().parse::<Spring>())
             .collect::<Result<_, _>>()?;
 
-        let springs = iter::repeat(folded_springs)
-            .take(UNFOLDING_FACTOR)
-            .intersperse(vec![Spring::Unknown])
-            .flatten()
-            .collect_vec();
-
         Ok(Self(springs))
     }
 }
@@ -188,14 +199,13 @@ impl fmt::Display for Springs {
 
 impl Springs {
     /// Check if the springs match the damaged groups pattern.
+    #[cfg(test)]
     fn is_valid(&self, damaged_spring_groups: &DamagedSpringGroups) -> bool {
         // Springs that contain `Spring::Unknown` can never be valid.
         if self.0.contains(&Spring::Unknown) {
-            // dbg!("early return because of unknown");
             return false;
         }
 
-        // TODO: remove unnecessary collect into vec, only used because comparing between &DamagingSpringGroup and DamagingSpringGroup is impossible
         let damaged_spring_groups_iter = self
             .0
             .iter()
@@ -212,83 +222,6 @@ impl Springs {
             damaged_spring_groups.0.iter(),
         )
     }
-
-    /// Check if start of some springs match the damaged groups pattern.
-    fn start_is_valid(&mut self, damaged_spring_groups: &DamagedSpringGroups) -> bool {
-        // TODO: inefficient check, could easily optimize out by using the type system better (i.e. making another spring that can never be unknown)
-        // Springs that contain `Spring::Unknown` can never be valid.
-        if self.0.contains(&Spring::Unknown) {
-            // dbg!("early exit due to unknown element");
-            unreachable!();
-            // return false;
-        }
-
-        // let group_cache_borrow = group_cache.borrow_mut();
-
-        // let last = self.0.pop();
-        // match group_cache_borrow.get(&self) {
-        //     Some(cached) => {
-        //         match last {
-        //             Some(last) => cached.last_mut().map(|(is_damaged, count)| match last {
-        //                 Spring::Damaged => {
-        //                     if *is_damaged {
-        //                         *count += 1
-        //                     } else {
-        //                         cached.push((true, 1))
-        //                     }
-        //                 }
-        //                 Spring::Operational => {
-        //                     if *is_damaged {
-        //                         cached.push((false, 1))
-        //                     } else {
-        //                         *count += 1
-        //                     }
-        //                 }
-        //                 Spring::Unknown => unreachable!(),
-        //             }),
-        //             None => todo!(),
-        //         };
-        //         // Push last element back.
-        //         if let Some(last) = last {
-        //             self.0.push(last);
-        //         }
-
-        //         //
-        //         todo!();
-        //     }
-        //     None => todo!(),
-        // }
-
-        let groups = self
-            .0
-            .iter()
-            // Group by predicate `Spring::is_damaged`:
-            // e.g. ###..## => [(damaged, count == 3), (not_damaged, count == 2), (damaged, count == 2))]
-            .group_by(|spring| spring.is_damaged());
-
-        let damaged_spring_groups_iter = groups
-            .into_iter()
-            .filter_map(|(is_damaged, group)| is_damaged.then_some(group))
-            .map(|group| DamagedSpringGroup(group.count()));
-
-        // General approach. Doesn't scale well, because checks for non-last
-        // elements are duplicated many times.
-        izip!(damaged_spring_groups_iter, damaged_spring_groups.0.iter())
-            .with_position()
-            .all(|(position, (a, b))| match position {
-                // The last damaged group may still be incomplete.
-                Position::Last | Position::Only => &a <= b,
-                _ => &a == b,
-            })
-
-        // Assume that all non-last groups were checked in previous calls to
-        // this function, so we only have to analyze the last element.
-        // let last = izip!(damaged_spring_groups_iter, damaged_spring_groups.0.iter()).last();
-        // match last {
-        //     Some((a, b)) => &a <= b,
-        //     None => true,
-        // }
-    }
 }
 
 #[test]
@@ -350,38 +283,40 @@ enum Spring {
     Unknown,
 }
 
-enum KnownSpring {
-    Operational,
-    Damaged,
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Vec<Line>> {
+    let lines = aoc_parse::parse_lines(puzzle_input)?;
+    Ok(lines)
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    // Collected so .progress() can be called on a fixed size iterator.
-    let lines = puzzle_input
-        .lines()
-        .map(|line| line.parse::<Line>().unwrap())
-        .collect_vec();
-
+/// Solve the problem for the parsed puzzle input and return the solution.
+///
+/// Each line is unfolded by `unfolding_factor` before its configurations are
+/// counted.
+pub fn solve_parsed(lines: Vec<Line>, unfolding_factor: usize) -> anyhow::Result<Solution> {
     let sum: usize = lines
-        .into_par_iter()
-        .progress()
-        // .inspect(|v| {
-        //     // dbg!(v);
-        //     println!("{}", v);
-        // })
-        .map(Line::all_valid_configurations)
+        .into_iter()
+        .map(|line| line.unfold(unfolding_factor).all_valid_configurations())
         .sum();
 
-    Ok(sum.to_string())
+    Ok(sum.into())
+}
+
+/// Solve the problem and return the solution.
+///
+/// Each line is unfolded by `unfolding_factor` before its configurations are
+/// counted.
+pub fn solve(puzzle_input: &str, unfolding_factor: usize) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?, unfolding_factor)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             ???.### 1,1,3
             .??..??...?##. 1,1,3
@@ -391,6 +326,6 @@ pub mod example {
             ?###???????? 3,2,1
         "};
         let expected_solution = 525152;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
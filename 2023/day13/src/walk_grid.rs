@@ -0,0 +1,225 @@
+use anyhow::{anyhow, ensure};
+use aoc_grid::Grid;
+use itertools::{FoldWhile, Itertools};
+use parse_display::{Display, FromStr};
+use std::str;
+
+/// A row or column of a `WalkGrid`, where the bit of each rock is set.
+type LineMask = u64;
+
+/// The grid we observe when taking our walk.
+#[derive(Debug, Display)]
+#[display("{grid}")]
+pub struct WalkGrid {
+    grid: Grid<Material>,
+    row_masks: Vec<LineMask>,
+    col_masks: Vec<LineMask>,
+}
+
+impl str::FromStr for WalkGrid {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid: Grid<Material> = s.parse()?;
+        ensure!(
+            grid.rows() <= LineMask::BITS as usize && grid.cols() <= LineMask::BITS as usize,
+            "expected at most {} rows and columns, but the grid has {} rows and {} columns",
+            LineMask::BITS,
+            grid.rows(),
+            grid.cols()
+        );
+
+        let row_masks = grid.iter_rows().map(line_mask).collect();
+        let col_masks = grid.iter_cols().map(line_mask).collect();
+
+        Ok(Self {
+            grid,
+            row_masks,
+            col_masks,
+        })
+    }
+}
+
+/// Encode a row or column, which must not be longer than `LineMask::BITS`.
+fn line_mask<'a>(line: impl Iterator<Item = &'a Material>) -> LineMask {
+    line.fold(0, |mask, material| {
+        (mask << 1) | LineMask::from(*material == Material::Rock)
+    })
+}
+
+impl WalkGrid {
+    /// Find all mirrors that reflect the grid after exactly `smudges` cells
+    /// have been corrected, i.e. where exactly `smudges` pairs of reflected
+    /// cells differ. Vertical mirrors come before horizontal ones, and mirrors
+    /// of the same axis are ordered by their index.
+    pub fn find_mirrors(&self, smudges: u32) -> Vec<Mirror> {
+        // A vertical mirror reflects columns onto each other, a horizontal
+        // mirror reflects rows.
+        let vertical_mirrors = find_mirror_indexes(&self.col_masks, smudges)
+            .map(|index| Mirror::new(index, MirrorAxis::Vertical));
+        let horizontal_mirrors = find_mirror_indexes(&self.row_masks, smudges)
+            .map(|index| Mirror::new(index, MirrorAxis::Horizontal));
+
+        vertical_mirrors.chain(horizontal_mirrors).collect()
+    }
+}
+
+/// Find the indexes of all mirrors between the `lines`, where a mirror at
+/// index `i` is between the lines `i - 1` and `i`, and the reflected lines
+/// differ in exactly `smudges` cells.
+fn find_mirror_indexes(lines: &[LineMask], smudges: u32) -> impl Iterator<Item = usize> + '_ {
+    // There are no mirrors where one of the sides is empty.
+    (1..lines.len()).filter(move |&mirror_idx| {
+        // mirror_idx = 5
+        //      |
+        // #.##..##.
+        // aaaaABbbb
+        //     ><
+        // left_side = [Aa...]
+        // right_side = [Bb...]
+        let left_side = lines[..mirror_idx].iter().rev();
+        let right_side = lines[mirror_idx..].iter();
+
+        let differing_cells = left_side
+            .zip(right_side)
+            .map(|(left, right)| (left ^ right).count_ones())
+            // Stop as soon as there are too many differing cells.
+            .fold_while(0, |differing_cells, differing_in_line| {
+                let differing_cells = differing_cells + differing_in_line;
+                if differing_cells > smudges {
+                    FoldWhile::Done(differing_cells)
+                } else {
+                    FoldWhile::Continue(differing_cells)
+                }
+            })
+            .into_inner();
+
+        differing_cells == smudges
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mirror {
+    /// The number of columns left of a vertical mirror, or the number of rows
+    /// above a horizontal mirror.
+    pub index: usize,
+    pub axis: MirrorAxis,
+}
+
+impl Mirror {
+    fn new(index: usize, axis: MirrorAxis) -> Self {
+        Self { index, axis }
+    }
+
+    /// The value of the mirror when summarizing the notes.
+    pub fn value(&self) -> usize {
+        match self.axis {
+            MirrorAxis::Vertical => self.index,
+            MirrorAxis::Horizontal => self.index * 100,
+        }
+    }
+}
+
+/// The axis that a mirror reflects perfectly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAxis {
+    /// The mirror is vertical, so all columns are reflected perfectly.
+    Vertical,
+    /// The mirror is horizontal, so all rows are reflected perfectly.
+    Horizontal,
+}
+
+#[derive(Debug, FromStr, Display, Clone, Copy, PartialEq, Eq)]
+enum Material {
+    #[display("#")]
+    Rock,
+
+    #[display(".")]
+    Ash,
+}
+
+/// Parse the notes, which are walk grids separated by blank lines.
+pub fn parse_notes(notes: &str) -> anyhow::Result<Vec<WalkGrid>> {
+    let walk_grids = notes
+        .split("\n\n")
+        .map(str::parse)
+        .collect::<Result<_, _>>()?;
+    Ok(walk_grids)
+}
+
+/// Summarize the notes by adding up the values of the mirrors of all walk
+/// grids, where each walk grid must have exactly one mirror with `smudges`
+/// differing cells.
+pub fn summarize_notes(walk_grids: &[WalkGrid], smudges: u32) -> anyhow::Result<usize> {
+    walk_grids
+        .iter()
+        .enumerate()
+        .map(|(grid_idx, walk_grid)| {
+            let mirrors = walk_grid.find_mirrors(smudges);
+            match mirrors.as_slice() {
+                [mirror] => Ok(mirror.value()),
+                _ => Err(anyhow!(
+                    "expected walk grid {} to have exactly one mirror with {} smudges, but found {:?}",
+                    grid_idx,
+                    smudges,
+                    mirrors
+                )),
+            }
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_find_mirror() -> anyhow::Result<()> {
+        //     4
+        //     |
+        //    ><
+        // ##.##.#
+        // .#.##.#
+        let walk_grid: WalkGrid = "##.##.#\n.#.##.#".parse()?;
+
+        assert_eq!(
+            vec![Mirror::new(4, MirrorAxis::Vertical)],
+            walk_grid.find_mirrors(0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_all_mirrors() -> anyhow::Result<()> {
+        let walk_grid: WalkGrid = indoc! {"
+            ##..
+            ##..
+            ##.#
+        "}
+        .trim_end()
+        .parse()?;
+
+        assert_eq!(
+            vec![
+                Mirror::new(1, MirrorAxis::Vertical),
+                Mirror::new(1, MirrorAxis::Horizontal),
+            ],
+            walk_grid.find_mirrors(0)
+        );
+        assert_eq!(
+            vec![
+                Mirror::new(3, MirrorAxis::Vertical),
+                Mirror::new(2, MirrorAxis::Horizontal),
+            ],
+            walk_grid.find_mirrors(1)
+        );
+        assert_eq!(Vec::<Mirror>::new(), walk_grid.find_mirrors(2));
+        assert_eq!(
+            vec![Mirror::new(2, MirrorAxis::Vertical)],
+            walk_grid.find_mirrors(5)
+        );
+
+        Ok(())
+    }
+}
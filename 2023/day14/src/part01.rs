@@ -1,58 +1,32 @@
+use aoc_grid::{Direction, Grid, PositionInGrid};
+use aoc_solution::Solution;
 use derive_new::new;
-use grid::Grid;
-use itertools::{Itertools, Position};
 use parse_display::{Display, FromStr};
-use std::{fmt, str};
+use std::str;
 use strum::EnumIs;
 
 /// A platform with a control panel with which it can be tilted in four
 /// directions.
-#[derive(Debug)]
-struct PlatformGrid(Grid<MaterialInGrid>);
+#[derive(Debug, Display)]
+#[display("{0}")]
+pub struct PlatformGrid(Grid<MaterialInGrid>);
 
 impl str::FromStr for PlatformGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cols = s
-            .lines()
-            .next()
-            .map_or(0, |first_row| first_row.chars().count());
-
-        let grid_vec = s
-            .lines()
-            .enumerate()
-            .flat_map(|(row, line)| {
-                line.chars().enumerate().map(move |(col, c)| {
-                    // TODO: remove unwrap from parse
-                    let material = c.to_string().as_str().parse::<Material>().unwrap();
-                    MaterialInGrid::new(material, PositionInGrid { row, col })
-                })
-            })
-            .collect_vec();
+        let grid = Grid::parse_with(s, |pos, c| {
+            let material = c.to_string().as_str().parse::<Material>()?;
+            Ok(MaterialInGrid::new(material, pos))
+        })?;
 
         // TODO: we'll be iterating over columns mostly for part 1, so make sure that is more efficient than iterating over rows.
-        Ok(Self(Grid::from_vec(grid_vec, cols)))
-    }
-}
-
-impl fmt::Display for PlatformGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (position, row) in self.0.iter_rows().with_position() {
-            for material in row {
-                write!(f, "{}", material)?;
-            }
-            if let Position::First | Position::Middle = position {
-                writeln!(f)?;
-            }
-        }
-        Ok(())
+        Ok(Self(grid))
     }
 }
 
 impl From<Vec<Vec<MaterialInGrid>>> for PlatformGrid {
     fn from(grid: Vec<Vec<MaterialInGrid>>) -> Self {
-        let cols = grid.first().map_or(0, |first| first.len());
-        Self(Grid::from_vec(grid.into_iter().flatten().collect(), cols))
+        Self(grid.into())
     }
 }
 
@@ -74,7 +48,7 @@ impl PlatformGrid {
         // TODO: optimization: we could skip checking the first row, as we will never be able to move any objects any further.
         for col_idx in 0..cols {
             for row_idx in 0..rows {
-                if let Some(material) = self.0.get(row_idx, col_idx) {
+                if let Some(material) = self.0.get(&PositionInGrid::new(row_idx, col_idx)) {
                     // We only want to move round rocks.
                     if !material.material.is_round_rock() {
                         continue;
@@ -116,29 +90,18 @@ impl PlatformGrid {
         }
 
         // TODO: we have to clone here because we can't get owned data from grid. The crate itself would have to provide this method to avoid cloning.
-        let (a, b) = match (
-            self.0.get(pos_a.row, pos_a.col),
-            self.0.get(pos_b.row, pos_b.col),
-        ) {
+        let (a, b) = match (self.0.get(&pos_a), self.0.get(&pos_b)) {
             (Some(a), Some(b)) => (a.material.clone(), b.material.clone()),
             _ => return false,
         };
 
-        self.0.get_mut(pos_a.row, pos_a.col).map(|e| e.material = b);
-        self.0.get_mut(pos_b.row, pos_b.col).map(|e| e.material = a);
+        self.0[pos_a].material = b;
+        self.0[pos_b].material = a;
 
         true
     }
 }
 
-type GridIndex = usize;
-
-#[derive(Debug, new, Clone, Copy, PartialEq, Eq)]
-struct PositionInGrid {
-    row: GridIndex,
-    col: GridIndex,
-}
-
 #[derive(Debug, new, Display, Clone)]
 #[display("{material}")]
 struct MaterialInGrid {
@@ -153,22 +116,14 @@ impl MaterialInGrid {
         let old_pos = self.position;
         let mut new_pos = old_pos;
 
-        loop {
-            let above_pos = Direction::North.translate(&new_pos);
-
-            // We can't go north any further.
-            if above_pos == new_pos {
-                break;
-            }
-
-            if let Some(above_material) = platform.0.get(above_pos.row, above_pos.col) {
-                if above_material.material.is_movable() {
+        // We can't go north any further once we would leave the grid or
+        // hit an immovable object.
+        while let Some(above_pos) = Direction::North.translate(&new_pos) {
+            match platform.0.get(&above_pos) {
+                Some(above_material) if above_material.material.is_movable() => {
                     new_pos = above_pos;
-                } else {
-                    break;
                 }
-            } else {
-                break;
+                _ => break,
             }
         }
 
@@ -194,66 +149,32 @@ impl Material {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
-enum Direction {
-    North,
-    _East,
-    _South,
-    _West,
-}
-
-impl Direction {
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes.
-    fn translate(&self, pos: &PositionInGrid) -> PositionInGrid {
-        let (row, col) = match self {
-            Direction::North => (pos.row.saturating_sub(1), pos.col),
-            Direction::_South => (pos.row + 1, pos.col),
-            Direction::_East => (pos.row, pos.col + 1),
-            Direction::_West => (pos.row, pos.col.saturating_sub(1)),
-        };
-        PositionInGrid { row, col }
-    }
-}
-
-#[test]
-fn test_translate_direction() {
-    assert_eq!(
-        PositionInGrid::new(0, 1),
-        Direction::North.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        PositionInGrid::new(2, 1),
-        Direction::_South.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        PositionInGrid::new(1, 2),
-        Direction::_East.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        PositionInGrid::new(1, 0),
-        Direction::_West.translate(&PositionInGrid::new(1, 1))
-    );
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<PlatformGrid> {
+    puzzle_input.parse()
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let mut platform: PlatformGrid = puzzle_input.parse()?;
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(mut platform: PlatformGrid) -> anyhow::Result<Solution> {
     platform.tilt_north();
 
-    println!("After tilting:\n{}", &platform);
-
     let total_load = platform.total_load();
 
-    Ok(total_load.to_string())
+    Ok(total_load.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             O....#....
             O.OO#....#
@@ -280,6 +201,6 @@ pub mod example {
         // #....#....
 
         let expected_solution = 136;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
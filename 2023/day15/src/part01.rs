@@ -1,7 +1,8 @@
+use aoc_solution::Solution;
 use std::str;
 
 #[derive(Debug)]
-struct HashableStrings(Vec<HashableString>);
+pub struct HashableStrings(Vec<HashableString>);
 
 impl str::FromStr for HashableStrings {
     type Err = anyhow::Error;
@@ -38,25 +39,34 @@ impl HashableString {
     }
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let hashable_strings: HashableStrings = puzzle_input.parse()?;
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<HashableStrings> {
+    puzzle_input.parse()
+}
 
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(hashable_strings: HashableStrings) -> anyhow::Result<Solution> {
     let sum_of_hashes: usize = hashable_strings.0.iter().map(HashableString::hash).sum();
 
-    Ok(sum_of_hashes.to_string())
+    Ok(sum_of_hashes.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
         "};
         let expected_solution = 1320;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
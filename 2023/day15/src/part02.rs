@@ -1,3 +1,5 @@
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_new::new;
 use parse_display::FromStr;
 use std::str;
@@ -106,15 +108,19 @@ impl LensLabel {
 struct FocalLength(usize);
 
 #[derive(Debug)]
-struct LensOperations(Vec<LensOperation>);
+pub struct LensOperations(Vec<LensOperation>);
 
 // TODO: parse_display should be able to automate this in the future
 impl str::FromStr for LensOperations {
-    type Err = anyhow::Error;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let lens_operations = s
             .split(',')
-            .map(str::parse::<LensOperation>)
+            .map(|operation| {
+                operation
+                    .parse::<LensOperation>()
+                    .map_err(|err| ParseError::at(s, operation, err.to_string()))
+            })
             .collect::<Result<_, _>>()?;
         Ok(Self(lens_operations))
     }
@@ -129,10 +135,14 @@ enum LensOperation {
     Remove(LensLabel),
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<LensOperations> {
+    Ok(puzzle_input.parse()?)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(lens_operations: LensOperations) -> anyhow::Result<Solution> {
     let mut boxes = Boxes::new();
-    let lens_operations: LensOperations = puzzle_input.parse()?;
 
     for lens_operation in lens_operations.0.into_iter() {
         match lens_operation {
@@ -145,19 +155,25 @@ pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
 
     let total_focusing_power = boxes.total_focusing_power();
 
-    Ok(total_focusing_power.to_string())
+    Ok(total_focusing_power.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {"
             rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7
         "};
         let expected_solution = 145;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
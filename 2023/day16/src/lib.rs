@@ -0,0 +1,42 @@
+pub mod part01;
+pub mod part02;
+
+/// The path of the puzzle input that is bundled with this day. It is only
+/// read at runtime, so the day builds even without it.
+pub const BUNDLED_PUZZLE_INPUT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/puzzle_input.txt");
+
+/// The accepted answers for the bundled puzzle input, one `part <n>: <answer>`
+/// line per part whose answer has been recorded.
+pub const ANSWERS: &str = include_str!("../answers.txt");
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use anyhow::Result;
+    use aoc_solution::Solution;
+
+    #[test]
+    fn test_part01_example() -> Result<()> {
+        test_example(part01::solve, part01::example::example_details)
+    }
+
+    #[test]
+    fn test_part02_example() -> Result<()> {
+        test_example(part02::solve, part02::example::example_details)
+    }
+
+    fn test_example<F, G>(solver: F, example_details: G) -> Result<()>
+    where
+        F: Fn(&str) -> Result<Solution>,
+        G: Fn() -> (&'static str, Solution),
+    {
+        let (puzzle_input_newline, expected_solution) = example_details();
+        let puzzle_input = puzzle_input_newline
+            .strip_suffix("\n")
+            .expect("there should be a newline at the end generated by indoc");
+
+        assert_eq!(expected_solution, solver(puzzle_input)?);
+
+        Ok(())
+    }
+}
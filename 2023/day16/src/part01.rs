@@ -1,48 +1,24 @@
+use aoc_grid::{Direction, Grid, PositionInGrid};
+use aoc_solution::Solution;
 use derive_new::new;
-use grid::Grid;
-use itertools::{Itertools, Position};
+use itertools::Itertools;
 use parse_display::{Display, FromStr};
 use std::{collections::HashSet, fmt, str};
 use strum::EnumIs;
 
-#[derive(Debug)]
-struct CaveFloorGrid(Grid<Tile>);
+#[derive(Debug, Display)]
+#[display("{0}")]
+pub struct CaveFloorGrid(Grid<Tile>);
 
 impl str::FromStr for CaveFloorGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cols = s
-            .lines()
-            .next()
-            .map_or(0, |first_row| first_row.chars().count());
-
-        let grid_vec = s
-            .lines()
-            .flat_map(|line| {
-                line.chars().map(|c| {
-                    // TODO: remove unwrap from parse
-                    let tile_type = c.to_string().as_str().parse::<TileType>().unwrap();
-                    Tile::new(tile_type)
-                })
-            })
-            .collect_vec();
+        let grid = Grid::parse_with(s, |_pos, c| {
+            let tile_type = c.to_string().as_str().parse::<TileType>()?;
+            Ok(Tile::new(tile_type))
+        })?;
 
-        Ok(Self(Grid::from_vec(grid_vec, cols)))
-    }
-}
-
-// TODO: grid should maybe provide a macro or similar to generate this boilerplate imo
-impl fmt::Display for CaveFloorGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (position, row) in self.0.iter_rows().with_position() {
-            for tile in row {
-                write!(f, "{}", tile)?;
-            }
-            if let Position::First | Position::Middle = position {
-                writeln!(f)?;
-            }
-        }
-        Ok(())
+        Ok(Self(grid))
     }
 }
 
@@ -75,7 +51,7 @@ struct Beam {
 impl Beam {
     fn move_once(&self, grid: &mut CaveFloorGrid) -> Vec<Self> {
         // Get the tile the beam is currently on.
-        if let Some(tile_under_beam) = grid.0.get_mut(self.position.row, self.position.col) {
+        if let Some(tile_under_beam) = grid.0.get_mut(&self.position) {
             // Only direct the beam if it hasn't passed through this tile before.
             if !tile_under_beam.seen_beams.contains(self) {
                 return tile_under_beam.direct_incoming_beam(self);
@@ -87,14 +63,6 @@ impl Beam {
     }
 }
 
-type GridIndex = usize;
-
-#[derive(Debug, new, Clone, Copy, PartialEq, Eq, Hash)]
-struct PositionInGrid {
-    row: GridIndex,
-    col: GridIndex,
-}
-
 #[derive(Debug, new)]
 struct Tile {
     /// The type of the tile.
@@ -204,7 +172,7 @@ impl TileType {
                 vec![Direction::West, Direction::East]
             }
             // The beam passes through this tile.
-            _ => vec![going_in_direction.clone()],
+            _ => vec![*going_in_direction],
         };
 
         new_directions
@@ -219,120 +187,34 @@ impl TileType {
     }
 }
 
-// TODO: maybe this could/should even derive Copy?
-#[derive(Debug, PartialEq, Eq, Clone, Hash, Display)]
-enum Direction {
-    #[display("^")]
-    North,
-
-    #[display(">")]
-    East,
-
-    #[display("v")]
-    South,
-
-    #[display("<")]
-    West,
-}
-
-impl Direction {
-    /// Invert the direction. Consider: if we were facing in this direction,
-    /// and turned around, in which direction would we be facing?
-    fn invert(&self) -> Self {
-        // TODO: implement smarter, this is too "manual".
-        match self {
-            Self::North => Self::South,
-            Self::South => Self::North,
-            Self::East => Self::West,
-            Self::West => Self::East,
-        }
-    }
-
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes. This will never return the same position back. If going
-    /// into some direction would be outside the grid bounds, return `None`.
-    fn translate(&self, pos: &PositionInGrid) -> Option<PositionInGrid> {
-        let (row, col) = match self {
-            Self::North => (pos.row.checked_sub(1)?, pos.col),
-            Self::South => (pos.row + 1, pos.col),
-            Self::East => (pos.row, pos.col + 1),
-            Self::West => (pos.row, pos.col.checked_sub(1)?),
-        };
-        Some(PositionInGrid { row, col })
-    }
-
-    /// Get the next direction, clockwise.
-    fn next_clockwise(&self) -> Direction {
-        // TODO: automate with strum/enum crate: get position of self in Direction::iter(), next clockwise is the next element.
-        match self {
-            Self::North => Self::East,
-            Self::East => Self::South,
-            Self::South => Self::West,
-            Self::West => Self::North,
-        }
-    }
-
-    /// Get the next direction, counter-clockwise.
-    fn next_counterclockwise(&self) -> Direction {
-        // TODO: automate with strum/enum crate: get position of self in Direction::iter(), next counter-clockwise is the previous element.
-        match self {
-            Self::North => Self::West,
-            Self::West => Self::South,
-            Self::South => Self::East,
-            Self::East => Self::North,
-        }
-    }
-
-    fn is_vertical(&self) -> bool {
-        matches!(self, Self::North | Self::South)
-    }
-
-    fn is_horizontal(&self) -> bool {
-        matches!(self, Self::West | Self::East)
-    }
-}
-
-#[test]
-fn test_translate_direction() {
-    assert_eq!(
-        Some(PositionInGrid::new(0, 1)),
-        Direction::North.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        Some(PositionInGrid::new(2, 1)),
-        Direction::South.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        Some(PositionInGrid::new(1, 2)),
-        Direction::East.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        Some(PositionInGrid::new(1, 0)),
-        Direction::West.translate(&PositionInGrid::new(1, 1))
-    );
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<CaveFloorGrid> {
+    puzzle_input.parse()
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let mut cave_floor_grid: CaveFloorGrid = puzzle_input.parse()?;
-
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(mut cave_floor_grid: CaveFloorGrid) -> anyhow::Result<Solution> {
     let initial_beam = Beam::new(PositionInGrid::new(0, 0), Direction::East);
 
     cave_floor_grid.beam_enters(initial_beam);
 
-    println!("{}", cave_floor_grid);
-
     let energized_tiles_count: usize = cave_floor_grid.energized_tiles().count();
 
-    Ok(energized_tiles_count.to_string())
+    Ok(energized_tiles_count.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {r#"
             .|...\....
             |.-.\.....
@@ -359,6 +241,6 @@ pub mod example {
         // .v//.|.v..
 
         let expected_solution = 46;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
@@ -1,48 +1,28 @@
+use anyhow::Context;
+use aoc_grid::{Direction, Grid, GridIndex, PositionInGrid};
+use aoc_solution::Solution;
 use derive_new::new;
-use grid::Grid;
-use itertools::{chain, Itertools, Position};
+use itertools::{chain, Itertools};
 use parse_display::{Display, FromStr};
-use std::{collections::HashSet, fmt, str};
+use pathfinding::directed::strongly_connected_components::strongly_connected_components;
+use std::{
+    collections::{HashMap, HashSet},
+    str,
+};
 use strum::EnumIs;
 
-#[derive(Debug, Clone)]
-struct CaveFloorGrid(Grid<Tile>);
+#[derive(Debug, Clone, Display)]
+#[display("{0}")]
+pub struct CaveFloorGrid(Grid<TileType>);
 
 impl str::FromStr for CaveFloorGrid {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let cols = s
-            .lines()
-            .next()
-            .map_or(0, |first_row| first_row.chars().count());
-
-        let grid_vec = s
-            .lines()
-            .flat_map(|line| {
-                line.chars().map(|c| {
-                    // TODO: remove unwrap from parse
-                    let tile_type = c.to_string().as_str().parse::<TileType>().unwrap();
-                    Tile::new(tile_type)
-                })
-            })
-            .collect_vec();
+        let grid = Grid::parse_with(s, |_pos, c| {
+            Ok(c.to_string().as_str().parse::<TileType>()?)
+        })?;
 
-        Ok(Self(Grid::from_vec(grid_vec, cols)))
-    }
-}
-
-// TODO: grid should maybe provide a macro or similar to generate this boilerplate imo
-impl fmt::Display for CaveFloorGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (position, row) in self.0.iter_rows().with_position() {
-            for tile in row {
-                write!(f, "{}", tile)?;
-            }
-            if let Position::First | Position::Middle = position {
-                writeln!(f)?;
-            }
-        }
-        Ok(())
+        Ok(Self(grid))
     }
 }
 
@@ -52,16 +32,6 @@ enum AxisIndex {
 }
 
 impl CaveFloorGrid {
-    /// Simulate how an initial beam (placed anywhere in the grid) spreads.
-    fn beam_enters(&mut self, starting_beam: Beam) {
-        let mut beams = vec![starting_beam];
-
-        while let Some(next_beam) = beams.pop() {
-            let next_beams = next_beam.move_once(self);
-            beams.extend(next_beams);
-        }
-    }
-
     /// Get all possible starting beams. Beams can start from any edge,
     /// moving/facing away from that edge.
     fn all_starting_edge_beams(&self) -> impl Iterator<Item = Beam> + '_ {
@@ -85,98 +55,251 @@ impl CaveFloorGrid {
     ) -> impl Iterator<Item = Beam> + '_ {
         self.0
             .indexed_iter()
-            .filter(move |((row, col), _)| match selected_axis {
-                AxisIndex::Row(selected_row) => &selected_row == row,
-                AxisIndex::Column(selected_col) => &selected_col == col,
+            .filter(move |(pos, _)| match selected_axis {
+                AxisIndex::Row(selected_row) => selected_row == pos.row,
+                AxisIndex::Column(selected_col) => selected_col == pos.col,
             })
-            .map(move |((row, col), _)| Beam::new(PositionInGrid { row, col }, facing_direction))
+            .map(move |(pos, _)| Beam::new(pos, facing_direction))
     }
 
-    fn energized_tiles(&self) -> impl Iterator<Item = &Tile> {
-        self.0.iter().filter(|tile| tile.is_energized())
+    /// Follow a single beam through empty tiles, mirrors and splitters it
+    /// passes through, until it is split, leaves the grid or starts looping
+    /// between mirrors.
+    fn trace_segment(&self, starting_beam: Beam) -> Segment {
+        let mut energized = EnergizedTiles::new(&self.0);
+        let mut seen_beams = HashSet::new();
+        let mut next_beam = Some(starting_beam);
+
+        while let Some(beam) = next_beam.take() {
+            let Some(tile_type) = self.0.get(&beam.position) else {
+                break;
+            };
+            if !seen_beams.insert(beam.clone()) {
+                break;
+            }
+
+            energized.insert(&beam.position);
+
+            if tile_type.splits(&beam) {
+                return Segment {
+                    energized,
+                    split_at: Some(beam.position),
+                };
+            }
+
+            // Non-splitting tiles direct a beam in at most one direction.
+            next_beam = tile_type.direct_incoming_beam(&beam).pop();
+        }
+
+        Segment {
+            energized,
+            split_at: None,
+        }
     }
-}
 
-#[derive(Debug, new, Clone, Hash, PartialEq, Eq, Display)]
-#[display("{facing_direction}")]
-struct Beam {
-    /// The position of the beam inside a 2D grid.
-    position: PositionInGrid,
+    /// The beams leaving the splitter at `splitter` once it splits a beam.
+    fn split_beams(&self, splitter: PositionInGrid) -> impl Iterator<Item = Beam> {
+        let split_beams = match self.0.get(&splitter) {
+            Some(tile_type @ TileType::Splitter(Splitter::Vertical)) => {
+                tile_type.direct_incoming_beam(&Beam::new(splitter, Direction::East))
+            }
+            Some(tile_type @ TileType::Splitter(Splitter::Horizontal)) => {
+                tile_type.direct_incoming_beam(&Beam::new(splitter, Direction::North))
+            }
+            _ => vec![],
+        };
+        split_beams.into_iter()
+    }
 
-    /// The direction the beam is currently pointing in.
-    facing_direction: Direction,
+    fn splitter_positions(&self) -> impl Iterator<Item = PositionInGrid> + '_ {
+        self.0
+            .indexed_iter()
+            .filter(|(_, tile_type)| tile_type.is_splitter())
+            .map(|(pos, _)| pos)
+    }
 }
 
-impl Beam {
-    fn move_once(&self, grid: &mut CaveFloorGrid) -> Vec<Self> {
-        // Get the tile the beam is currently on.
-        if let Some(tile_under_beam) = grid.0.get_mut(self.position.row, self.position.col) {
-            // Only direct the beam if it hasn't passed through this tile before.
-            if !tile_under_beam.seen_beams.contains(self) {
-                return tile_under_beam.direct_incoming_beam(self);
-            }
+/// A set of energized tiles of a grid, stored as one bit per tile, so that
+/// sets can be merged quickly.
+#[derive(Debug, Clone)]
+struct EnergizedTiles {
+    bits: Vec<u64>,
+    cols: usize,
+}
+
+impl EnergizedTiles {
+    fn new<T>(grid: &Grid<T>) -> Self {
+        let (rows, cols) = grid.size();
+        Self {
+            bits: vec![0; (rows * cols).div_ceil(u64::BITS as usize)],
+            cols,
         }
+    }
 
-        // TODO: probably inefficient, better to return Option<Vec1> (never empty anyways) or Option<Vec>
-        vec![]
+    fn insert(&mut self, pos: &PositionInGrid) {
+        let idx = pos.row * self.cols + pos.col;
+        self.bits[idx / u64::BITS as usize] |= 1 << (idx % u64::BITS as usize);
     }
-}
 
-type GridIndex = usize;
+    fn union_with(&mut self, other: &Self) {
+        for (bits, other_bits) in self.bits.iter_mut().zip(&other.bits) {
+            *bits |= other_bits;
+        }
+    }
 
-#[derive(Debug, new, Clone, Copy, PartialEq, Eq, Hash)]
-struct PositionInGrid {
-    row: GridIndex,
-    col: GridIndex,
+    fn len(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|bits| bits.count_ones() as usize)
+            .sum()
+    }
 }
 
-#[derive(Debug, new, Clone)]
-struct Tile {
-    /// The type of the tile.
-    tile_type: TileType,
+/// The tiles a single beam energizes before it is split.
+struct Segment {
+    energized: EnergizedTiles,
+
+    /// The position of the splitter that split the beam, if any.
+    split_at: Option<PositionInGrid>,
+}
 
-    /// All beams that have passed been "seen" by this tile, which
-    /// includes all beams that have passed through, been reflected in, or
-    /// been split in this tile.
-    #[new(default)]
-    seen_beams: HashSet<Beam>,
+/// A graph whose nodes are the splitters of a grid, where each splitter is
+/// connected to the splitters its two split beams run into. Since a splitter
+/// splits a beam the same way no matter from which side the beam hits it, all
+/// tiles energized from a splitter can be precomputed once and shared between
+/// all starting beams. Splitters that split beams into each other in a loop
+/// (a strongly connected component) energize the same tiles, so they are
+/// collapsed into one node first.
+struct BeamGraph {
+    /// The index of the strongly connected component of each splitter.
+    component_of_splitter: HashMap<PositionInGrid, usize>,
+
+    /// All tiles energized by a beam being split in a component.
+    energized_from_component: Vec<EnergizedTiles>,
 }
 
-impl fmt::Display for Tile {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let tile = match self.tile_type {
-            TileType::Empty => {
-                let seen_beams_len = self.seen_beams.len();
-
-                if seen_beams_len > 1 {
-                    seen_beams_len.to_string()
-                } else if let Some(first) = self.seen_beams.iter().next() {
-                    first.to_string()
-                } else {
-                    self.tile_type.to_string()
+impl BeamGraph {
+    fn new(grid: &CaveFloorGrid) -> Self {
+        let splitters = grid.splitter_positions().collect_vec();
+
+        // The tiles energized by the split beams of each splitter up until
+        // they are split again, and the splitters they are split by.
+        let (splitter_energized, splitter_successors): (HashMap<_, _>, HashMap<_, _>) = splitters
+            .iter()
+            .map(|&splitter| {
+                let mut energized = EnergizedTiles::new(&grid.0);
+                let mut successors = Vec::new();
+                for segment in grid
+                    .split_beams(splitter)
+                    .map(|beam| grid.trace_segment(beam))
+                {
+                    energized.union_with(&segment.energized);
+                    successors.extend(segment.split_at);
                 }
+                ((splitter, energized), (splitter, successors))
+            })
+            .unzip();
+
+        let components = strongly_connected_components(&splitters, |splitter| {
+            splitter_successors[splitter].clone()
+        });
+        let component_of_splitter: HashMap<PositionInGrid, usize> = components
+            .iter()
+            .enumerate()
+            .flat_map(|(component, splitters)| {
+                splitters.iter().map(move |&splitter| (splitter, component))
+            })
+            .collect();
+
+        // Collapse each component into a single node of a directed acyclic
+        // graph.
+        let (component_energized, component_successors): (Vec<_>, Vec<_>) = components
+            .iter()
+            .enumerate()
+            .map(|(component, splitters)| {
+                let mut energized = EnergizedTiles::new(&grid.0);
+                for splitter in splitters {
+                    energized.union_with(&splitter_energized[splitter]);
+                }
+                let successors = splitters
+                    .iter()
+                    .flat_map(|splitter| &splitter_successors[splitter])
+                    .map(|successor| component_of_splitter[successor])
+                    .filter(|&successor| successor != component)
+                    .unique()
+                    .collect_vec();
+                (energized, successors)
+            })
+            .unzip();
+
+        let mut energized_from_component = vec![None; components.len()];
+        for component in 0..components.len() {
+            Self::energize_from_component(
+                component,
+                &component_energized,
+                &component_successors,
+                &mut energized_from_component,
+            );
+        }
+
+        Self {
+            component_of_splitter,
+            energized_from_component: energized_from_component.into_iter().flatten().collect(),
+        }
+    }
+
+    /// Compute all tiles energized from a `component` (and all components
+    /// reachable from it), memoizing the results in `energized_from`.
+    fn energize_from_component(
+        component: usize,
+        component_energized: &[EnergizedTiles],
+        component_successors: &[Vec<usize>],
+        energized_from: &mut Vec<Option<EnergizedTiles>>,
+    ) {
+        if energized_from[component].is_some() {
+            return;
+        }
+
+        let mut energized = component_energized[component].clone();
+        for &successor in &component_successors[component] {
+            Self::energize_from_component(
+                successor,
+                component_energized,
+                component_successors,
+                energized_from,
+            );
+            if let Some(successor_energized) = &energized_from[successor] {
+                energized.union_with(successor_energized);
             }
-            _ => self.tile_type.to_string(),
-        };
+        }
 
-        write!(f, "{}", tile)?;
+        energized_from[component] = Some(energized);
+    }
+
+    /// Count all tiles energized by a `starting_beam`.
+    fn energized_tile_count(&self, grid: &CaveFloorGrid, starting_beam: Beam) -> usize {
+        let Segment {
+            mut energized,
+            split_at,
+        } = grid.trace_segment(starting_beam);
+
+        if let Some(splitter) = split_at {
+            let component = self.component_of_splitter[&splitter];
+            energized.union_with(&self.energized_from_component[component]);
+        }
 
-        Ok(())
+        energized.len()
     }
 }
 
-impl Tile {
-    /// Where is an incoming beam directed, or how is it transformed, when
-    /// it goes through this tile. This tile also memorizes that the incoming
-    /// beam has passed through it.
-    fn direct_incoming_beam(&mut self, beam: &Beam) -> Vec<Beam> {
-        self.seen_beams.insert(beam.clone());
-        self.tile_type.direct_incoming_beam(beam)
-    }
+#[derive(Debug, new, Clone, Hash, PartialEq, Eq, Display)]
+#[display("{facing_direction}")]
+struct Beam {
+    /// The position of the beam inside a 2D grid.
+    position: PositionInGrid,
 
-    fn is_energized(&self) -> bool {
-        !self.seen_beams.is_empty()
-    }
+    /// The direction the beam is currently pointing in.
+    facing_direction: Direction,
 }
 
 #[derive(Debug, FromStr, Display, EnumIs, Clone)]
@@ -210,6 +333,15 @@ enum Splitter {
 }
 
 impl TileType {
+    /// Whether an incoming beam is split into two beams by this tile.
+    fn splits(&self, beam: &Beam) -> bool {
+        match self {
+            TileType::Splitter(Splitter::Vertical) => beam.facing_direction.is_horizontal(),
+            TileType::Splitter(Splitter::Horizontal) => beam.facing_direction.is_vertical(),
+            _ => false,
+        }
+    }
+
     /// Where is an incoming beam directed, or how is it transformed, when
     /// it goes through this tile.
     fn direct_incoming_beam(&self, beam: &Beam) -> Vec<Beam> {
@@ -254,125 +386,36 @@ impl TileType {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, Display)]
-enum Direction {
-    #[display("^")]
-    North,
-
-    #[display(">")]
-    East,
-
-    #[display("v")]
-    South,
-
-    #[display("<")]
-    West,
-}
-
-impl Direction {
-    /// Invert the direction. Consider: if we were facing in this direction,
-    /// and turned around, in which direction would we be facing?
-    fn invert(&self) -> Self {
-        // TODO: implement smarter, this is too "manual".
-        match self {
-            Self::North => Self::South,
-            Self::South => Self::North,
-            Self::East => Self::West,
-            Self::West => Self::East,
-        }
-    }
-
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes. This will never return the same position back. If going
-    /// into some direction would be outside the grid bounds, return `None`.
-    fn translate(&self, pos: &PositionInGrid) -> Option<PositionInGrid> {
-        let (row, col) = match self {
-            Self::North => (pos.row.checked_sub(1)?, pos.col),
-            Self::South => (pos.row + 1, pos.col),
-            Self::East => (pos.row, pos.col + 1),
-            Self::West => (pos.row, pos.col.checked_sub(1)?),
-        };
-        Some(PositionInGrid { row, col })
-    }
-
-    /// Get the next direction, clockwise.
-    fn next_clockwise(&self) -> Direction {
-        // TODO: automate with strum/enum crate: get position of self in Direction::iter(), next clockwise is the next element.
-        match self {
-            Self::North => Self::East,
-            Self::East => Self::South,
-            Self::South => Self::West,
-            Self::West => Self::North,
-        }
-    }
-
-    /// Get the next direction, counter-clockwise.
-    fn next_counterclockwise(&self) -> Direction {
-        // TODO: automate with strum/enum crate: get position of self in Direction::iter(), next counter-clockwise is the previous element.
-        match self {
-            Self::North => Self::West,
-            Self::West => Self::South,
-            Self::South => Self::East,
-            Self::East => Self::North,
-        }
-    }
-
-    fn is_vertical(&self) -> bool {
-        matches!(self, Self::North | Self::South)
-    }
-
-    fn is_horizontal(&self) -> bool {
-        matches!(self, Self::West | Self::East)
-    }
-}
-
-#[test]
-fn test_translate_direction() {
-    assert_eq!(
-        Some(PositionInGrid::new(0, 1)),
-        Direction::North.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        Some(PositionInGrid::new(2, 1)),
-        Direction::South.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        Some(PositionInGrid::new(1, 2)),
-        Direction::East.translate(&PositionInGrid::new(1, 1))
-    );
-    assert_eq!(
-        Some(PositionInGrid::new(1, 0)),
-        Direction::West.translate(&PositionInGrid::new(1, 1))
-    );
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<CaveFloorGrid> {
+    puzzle_input.parse()
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let cave_floor_grid: CaveFloorGrid = puzzle_input.parse()?;
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(cave_floor_grid: CaveFloorGrid) -> anyhow::Result<Solution> {
+    let beam_graph = BeamGraph::new(&cave_floor_grid);
 
-    let (max_energized_tile_count, max_cave_floor_grid) = cave_floor_grid
+    let max_energized_tile_count = cave_floor_grid
         .all_starting_edge_beams()
-        .map(|starting_beam| {
-            let mut cave_floor_grid_cloned = cave_floor_grid.clone();
-            cave_floor_grid_cloned.beam_enters(starting_beam);
-            let energized_tile_count: usize = cave_floor_grid_cloned.energized_tiles().count();
-
-            (energized_tile_count, cave_floor_grid_cloned)
-        })
-        .max_by(|(count_a, _), (count_b, _)| count_a.cmp(count_b))
-        .expect("expected there to be at least one possible starting beam");
+        .map(|starting_beam| beam_graph.energized_tile_count(&cave_floor_grid, starting_beam))
+        .max()
+        .context("expected there to be at least one possible starting beam")?;
 
-    println!("{}", max_cave_floor_grid);
+    Ok(max_energized_tile_count.into())
+}
 
-    Ok(max_energized_tile_count.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide the example details as `(puzzle input, expected solution)`.
-    pub fn example_details() -> (&'static str, String) {
+    pub fn example_details() -> (&'static str, Solution) {
         let puzzle_input = indoc! {r#"
             .|...\....
             |.-.\.....
@@ -403,6 +446,6 @@ pub mod example {
         // .v//.|.v..
 
         let expected_solution = 51;
-        (puzzle_input, expected_solution.to_string())
+        (puzzle_input, expected_solution.into())
     }
 }
@@ -0,0 +1,431 @@
+use aoc_grid::{Direction, Grid, PositionInGrid};
+use derive_new::new;
+use itertools::Itertools;
+use parse_display::{Display, FromStr};
+use pathfinding::prelude::astar;
+use std::{collections::HashMap, iter, str};
+use strum::IntoEnumIterator;
+
+#[derive(Debug, Display)]
+#[display("{0}")]
+pub struct CityBlockGrid(Grid<CityBlockInGrid>);
+
+impl str::FromStr for CityBlockGrid {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Grid::parse_with(s, |pos, c| {
+            let city_block = c.to_string().as_str().parse::<CityBlock>()?;
+            Ok(CityBlockInGrid::new(city_block, pos))
+        })?;
+
+        Ok(Self(grid))
+    }
+}
+
+impl CityBlockGrid {
+    /// The position of the top left city block.
+    pub fn top_left(&self) -> PositionInGrid {
+        PositionInGrid::new(0, 0)
+    }
+
+    /// The position of the bottom right city block.
+    pub fn bottom_right(&self) -> PositionInGrid {
+        let (rows, cols) = self.0.size();
+        PositionInGrid::new(rows.saturating_sub(1), cols.saturating_sub(1))
+    }
+
+    /// Find the path with the least heat loss that a crucible moving according
+    /// to the `rules` can take. The heat loss of the starting city block is not
+    /// incurred, since the crucible starts there.
+    pub fn find_shortest_path(&self, rules: &MovementRules) -> Option<CruciblePath> {
+        if !self.0.contains(&rules.start) || !self.0.contains(&rules.end) {
+            return None;
+        }
+
+        // A* needs a heuristic that never overestimates the remaining heat
+        // loss: every further block costs at least the smallest heat loss.
+        let min_heat_loss = self
+            .0
+            .iter()
+            .map(|city_block| city_block.city_block.heat_loss)
+            .min()
+            .unwrap_or(0);
+
+        let start = Crucible::new(rules.start, None, 0);
+        let successors = |c: &Crucible| c.next_possible_moves(self, rules);
+        let heuristic = |c: &Crucible| c.position.manhattan_distance(&rules.end) * min_heat_loss;
+        let success = |c: &Crucible| c.can_stop(rules);
+
+        let (crucibles, total_heat_loss) = astar(&start, successors, heuristic, success)?;
+
+        // `astar`s return value includes the starting node, which didn't move.
+        let steps = crucibles
+            .into_iter()
+            .filter_map(|crucible| {
+                Some(PathStep {
+                    position: crucible.position,
+                    direction: crucible.facing_direction?,
+                    heat_loss: self.0.get(&crucible.position)?.city_block.heat_loss,
+                })
+            })
+            .collect();
+
+        Some(CruciblePath {
+            start: rules.start,
+            steps,
+            total_heat_loss,
+        })
+    }
+}
+
+/// A single move of a crucible along a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathStep {
+    /// The position of the city block the crucible moved onto.
+    pub position: PositionInGrid,
+
+    /// The direction the crucible moved in.
+    pub direction: Direction,
+
+    /// The heat lost by moving onto the city block.
+    pub heat_loss: HeatLoss,
+}
+
+/// The path of a crucible through the city, from its start to its end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CruciblePath {
+    pub start: PositionInGrid,
+    pub steps: Vec<PathStep>,
+    pub total_heat_loss: HeatLoss,
+}
+
+impl CruciblePath {
+    /// All positions on the path in order, including the start.
+    pub fn positions(&self) -> impl Iterator<Item = PositionInGrid> + '_ {
+        iter::once(self.start).chain(self.steps.iter().map(|step| step.position))
+    }
+
+    /// All moves along the path in order.
+    pub fn moves(&self) -> impl Iterator<Item = Direction> + '_ {
+        self.steps.iter().map(|step| step.direction)
+    }
+
+    /// Render the `city_block_grid` with the path drawn on top as the
+    /// directions of the moves, in bold red, and the start in bold green,
+    /// using ANSI escape codes.
+    pub fn to_ansi_overlay(&self, city_block_grid: &CityBlockGrid) -> String {
+        const START: &str = "\x1b[1;32m";
+        const PATH: &str = "\x1b[1;31m";
+        const RESET: &str = "\x1b[0m";
+
+        self.overlay(city_block_grid, |cell, on_path| match on_path {
+            OnPath::Start => format!("{}{}{}", START, cell, RESET),
+            OnPath::Step(direction) => format!("{}{}{}", PATH, direction, RESET),
+            OnPath::No => cell.to_string(),
+        })
+    }
+
+    /// Render the path as plain text with one line per move, so that paths
+    /// can be saved to files and diffed. The first line contains the start,
+    /// every further line a move as `<row>,<col> <direction> <heat loss>`,
+    /// and the last line the total heat loss.
+    pub fn to_plain_text(&self) -> String {
+        let start = format!("start {},{}", self.start.row, self.start.col);
+        let steps = self.steps.iter().map(|step| {
+            format!(
+                "{},{} {} {}",
+                step.position.row, step.position.col, step.direction, step.heat_loss
+            )
+        });
+        let total = format!("total {}", self.total_heat_loss);
+
+        iter::once(start)
+            .chain(steps)
+            .chain(iter::once(total))
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    /// Render every city block of the grid with `render`, given whether and
+    /// how the block lies on this path.
+    fn overlay<F>(&self, city_block_grid: &CityBlockGrid, mut render: F) -> String
+    where
+        F: FnMut(&CityBlockInGrid, OnPath) -> String,
+    {
+        // Provide easier/faster (random) access to the path. If a block is
+        // visited more than once, its last move is drawn.
+        let moves: HashMap<PositionInGrid, Direction> = self
+            .steps
+            .iter()
+            .map(|step| (step.position, step.direction))
+            .collect();
+
+        city_block_grid
+            .0
+            .iter_rows()
+            .map(|row| {
+                row.map(|city_block| {
+                    let on_path = match moves.get(&city_block.position) {
+                        Some(&direction) => OnPath::Step(direction),
+                        None if city_block.position == self.start => OnPath::Start,
+                        None => OnPath::No,
+                    };
+                    render(city_block, on_path)
+                })
+                .collect::<String>()
+            })
+            .join("\n")
+    }
+}
+
+/// Whether and how a city block lies on a `CruciblePath`.
+enum OnPath {
+    Start,
+    Step(Direction),
+    No,
+}
+
+/// The rules according to which a crucible may move through the city.
+#[derive(Debug, Clone)]
+pub struct MovementRules {
+    /// The minimum number of blocks a crucible must move in a direction
+    /// before it can turn, reverse or stop at the end.
+    pub min_straight: usize,
+
+    /// The maximum number of blocks a crucible may move in a direction
+    /// without turning.
+    pub max_straight: usize,
+
+    /// Whether a crucible may turn 90 degrees counterclockwise, once it has
+    /// moved at least `min_straight` blocks in its direction.
+    pub can_turn_left: bool,
+
+    /// Whether a crucible may turn 90 degrees clockwise, once it has moved at
+    /// least `min_straight` blocks in its direction.
+    pub can_turn_right: bool,
+
+    /// Whether a crucible may turn around and move back into the city block
+    /// it just came from. Just like turning, this is only possible once it
+    /// has moved at least `min_straight` blocks in its direction, and it then
+    /// has to move `min_straight` blocks in the opposite direction before it
+    /// can turn or reverse again.
+    pub can_reverse: bool,
+
+    pub start: PositionInGrid,
+    pub end: PositionInGrid,
+}
+
+impl MovementRules {
+    /// A regular crucible, which can move at most three blocks in a single
+    /// direction before it must turn 90 degrees left or right.
+    pub fn crucible(start: PositionInGrid, end: PositionInGrid) -> Self {
+        Self {
+            min_straight: 1,
+            max_straight: 3,
+            can_turn_left: true,
+            can_turn_right: true,
+            can_reverse: false,
+            start,
+            end,
+        }
+    }
+
+    /// An ultra crucible, which needs to move a minimum of four blocks in a
+    /// direction before it can turn or stop at the end, and can move a
+    /// maximum of ten consecutive blocks without turning.
+    pub fn ultra_crucible(start: PositionInGrid, end: PositionInGrid) -> Self {
+        Self {
+            min_straight: 4,
+            max_straight: 10,
+            ..Self::crucible(start, end)
+        }
+    }
+}
+
+/// The object that moves through the grid, aiming to find the path with
+/// minimum heat loss.
+#[derive(Debug, new, PartialEq, Eq, Hash, Clone)]
+struct Crucible {
+    position: PositionInGrid,
+    /// The direction of the crucible's last move, which is `None` before it
+    /// has moved at all.
+    facing_direction: Option<Direction>,
+    /// The number of consecutive moves the crucible has made in the same
+    /// direction. Once the crucible changes direction, this is reset to the
+    /// minimum value 1.
+    consecutive_same_direction_moves: usize,
+}
+
+impl Crucible {
+    /// Whether the crucible may stop at its current position.
+    fn can_stop(&self, rules: &MovementRules) -> bool {
+        self.position == rules.end
+            && (self.facing_direction.is_none()
+                || self.consecutive_same_direction_moves >= rules.min_straight)
+    }
+
+    /// Given this crucible object, what are all possible moves it can make?
+    fn next_possible_moves(
+        &self,
+        grid: &CityBlockGrid,
+        rules: &MovementRules,
+    ) -> Vec<(Crucible, HeatLoss)> {
+        let next_moves = match self.facing_direction {
+            // The crucible can start moving in any direction.
+            None => Direction::iter()
+                .map(|direction| (direction, 1))
+                .collect_vec(),
+            Some(facing_direction) => {
+                let can_change_direction =
+                    self.consecutive_same_direction_moves >= rules.min_straight;
+                [
+                    (can_change_direction && rules.can_turn_left)
+                        .then_some((facing_direction.next_counterclockwise(), 1)),
+                    (can_change_direction && rules.can_turn_right)
+                        .then_some((facing_direction.next_clockwise(), 1)),
+                    (can_change_direction && rules.can_reverse)
+                        .then_some((facing_direction.invert(), 1)),
+                    (self.consecutive_same_direction_moves < rules.max_straight)
+                        .then_some((facing_direction, self.consecutive_same_direction_moves + 1)),
+                ]
+                .into_iter()
+                .flatten()
+                .collect_vec()
+            }
+        };
+
+        next_moves
+            .into_iter()
+            .filter_map(|(next_facing_direction, new_consecutive_straight_moves)| {
+                // Only add the next position if it is in the bounds of the grid.
+                let next_position = next_facing_direction.translate(&self.position)?;
+                let city_block = grid.0.get(&next_position)?;
+
+                Some((
+                    Self::new(
+                        next_position,
+                        Some(next_facing_direction),
+                        new_consecutive_straight_moves,
+                    ),
+                    city_block.city_block.heat_loss,
+                ))
+            })
+            .collect_vec()
+    }
+}
+
+#[derive(Debug, new, Display, Clone)]
+#[display("{city_block}")]
+struct CityBlockInGrid {
+    city_block: CityBlock,
+    position: PositionInGrid,
+}
+
+#[derive(Debug, FromStr, Display, Clone)]
+struct CityBlock {
+    heat_loss: HeatLoss,
+}
+
+pub type HeatLoss = usize;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_one_sided_turns() -> anyhow::Result<()> {
+        let grid: CityBlockGrid = indoc! {"
+            191
+            111
+        "}
+        .trim_end()
+        .parse()?;
+
+        let rules = MovementRules {
+            min_straight: 1,
+            max_straight: 3,
+            can_turn_left: false,
+            can_turn_right: false,
+            can_reverse: false,
+            start: grid.top_left(),
+            end: PositionInGrid::new(0, 2),
+        };
+
+        // Without turning, the crucible can only go straight to the end.
+        let path = grid.find_shortest_path(&rules).expect("path exists");
+        assert_eq!(10, path.total_heat_loss);
+
+        // Turning right only doesn't help to go around the expensive block.
+        let right_only = MovementRules {
+            can_turn_right: true,
+            ..rules.clone()
+        };
+        let path = grid.find_shortest_path(&right_only).expect("path exists");
+        assert_eq!(10, path.total_heat_loss);
+
+        // Turning left only does: down, left turn, right, left turn, up.
+        let left_only = MovementRules {
+            can_turn_left: true,
+            ..rules
+        };
+        let path = grid.find_shortest_path(&left_only).expect("path exists");
+        assert_eq!(4, path.total_heat_loss);
+        assert_eq!(
+            vec![
+                Direction::South,
+                Direction::East,
+                Direction::East,
+                Direction::North
+            ],
+            path.moves().collect_vec()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_exports() -> anyhow::Result<()> {
+        let grid: CityBlockGrid = indoc! {"
+            191
+            111
+        "}
+        .trim_end()
+        .parse()?;
+
+        let rules = MovementRules::crucible(grid.top_left(), grid.bottom_right());
+        let path = grid.find_shortest_path(&rules).expect("path exists");
+
+        assert_eq!(
+            vec![
+                PositionInGrid::new(0, 0),
+                PositionInGrid::new(1, 0),
+                PositionInGrid::new(1, 1),
+                PositionInGrid::new(1, 2),
+            ],
+            path.positions().collect_vec()
+        );
+        assert_eq!(
+            path.total_heat_loss,
+            path.steps
+                .iter()
+                .map(|step| step.heat_loss)
+                .sum::<HeatLoss>()
+        );
+
+        let plain_text = indoc! {"
+            start 0,0
+            1,0 v 1
+            1,1 > 1
+            1,2 > 1
+            total 3
+        "};
+        assert_eq!(plain_text, path.to_plain_text());
+
+        let ansi_overlay =
+            "\x1b[1;32m1\x1b[0m91\n\x1b[1;31mv\x1b[0m\x1b[1;31m>\x1b[0m\x1b[1;31m>\x1b[0m";
+        assert_eq!(ansi_overlay, path.to_ansi_overlay(&grid));
+
+        Ok(())
+    }
+}
@@ -1,44 +1,64 @@
-mod part01;
-mod part02;
+use anyhow::Context;
+use aoc_input::InputArg;
+use clap::{Parser, ValueEnum};
+use day17::{city_block_grid::CityBlockGrid, part01, part02, BUNDLED_PUZZLE_INPUT};
+use std::{fs, path::PathBuf};
 
-fn main() -> anyhow::Result<()> {
-    let puzzle_input = include_str!("../puzzle_input.txt");
+#[derive(Debug, Parser)]
+#[command(about = "Solve both parts of an Advent of Code puzzle")]
+struct Cli {
+    #[command(flatten)]
+    input: InputArg,
 
-    println!("Part 01: {}", part01::solve(&puzzle_input)?);
-    println!("Part 02: {}", part02::solve(&puzzle_input)?);
+    /// Also render the path with the least heat loss of each part.
+    #[arg(long)]
+    render: Option<Render>,
 
-    Ok(())
+    /// Write the rendered paths to this file instead of stdout.
+    #[arg(long, requires = "render")]
+    output: Option<PathBuf>,
 }
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use anyhow::Result;
+/// How to render a path.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Render {
+    /// The city blocks with the path drawn on top, using ANSI colors.
+    Ansi,
+    /// One line per move, which can be saved and diffed.
+    Plain,
+}
 
-    #[test]
-    fn test_part01_example() -> Result<()> {
-        test_example(part01::solve, part01::example::example_details)
-    }
+fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let puzzle_input = cli.input.read(BUNDLED_PUZZLE_INPUT)?;
 
-    #[test]
-    fn test_part02_example() -> Result<()> {
-        test_example(part02::solve, part02::example::example_details)
-    }
+    println!("Part 01: {}", part01::solve(&puzzle_input)?);
+    println!("Part 02: {}", part02::solve(&puzzle_input)?);
 
-    fn test_example<F, G, I>(solver: F, example_details: G) -> Result<()>
-    where
-        F: Fn(&str) -> Result<String>,
-        I: Iterator<Item = (&'static str, String)>,
-        G: Fn() -> I,
-    {
-        for (puzzle_input_newline, expected_solution) in example_details() {
-            let puzzle_input = puzzle_input_newline
-                .strip_suffix("\n")
-                .expect("there should be a newline at the end generated by indoc");
-
-            assert_eq!(expected_solution, solver(puzzle_input)?);
-        }
+    if let Some(render) = cli.render {
+        let city_block_grid: CityBlockGrid = part01::parse(&puzzle_input)?;
+        let paths = [
+            ("Part 01", part01::shortest_path(&city_block_grid)?),
+            ("Part 02", part02::shortest_path(&city_block_grid)?),
+        ];
+
+        let rendered: String = paths
+            .iter()
+            .map(|(label, path)| {
+                let rendered = match render {
+                    Render::Ansi => path.to_ansi_overlay(&city_block_grid) + "\n",
+                    Render::Plain => path.to_plain_text(),
+                };
+                format!("{} path:\n{}", label, rendered)
+            })
+            .collect();
 
-        Ok(())
+        match &cli.output {
+            Some(path) => fs::write(path, rendered)
+                .with_context(|| format!("failed to write the paths to {}", path.display()))?,
+            None => print!("{}", rendered),
+        }
     }
+
+    Ok(())
 }
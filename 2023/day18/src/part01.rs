@@ -1,18 +1,18 @@
+use aoc_grid::{Direction, Grid, GridIndex, PositionInGrid, SignedPositionInGrid};
+use aoc_solution::Solution;
 use derive_new::new;
-use grid::Grid;
-use itertools::{Itertools, Position};
 use parse_display::{Display, FromStr};
-use std::{fmt, str};
-use strum::{EnumIs, EnumIter, IntoEnumIterator};
+use std::str;
+use strum::{EnumIs, IntoEnumIterator};
 use vec1::vec1;
 
 #[derive(Debug)]
-struct DigPlan(Vec<DigInstruction>);
+pub struct DigPlan(Vec<DigInstruction>);
 
 impl str::FromStr for DigPlan {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let dig_insts = s.lines().map(str::parse).collect::<Result<_, _>>()?;
+        let dig_insts = aoc_parse::parse_lines(s)?;
         Ok(Self(dig_insts))
     }
 }
@@ -56,17 +56,14 @@ impl DigPlan {
         let cols = last_col.abs_diff(first_col) + 1;
 
         // Build up an empty grid filled with `Terrain::GroundLevel`.
-        let grid_vec = (0..rows)
-            .flat_map(|row| {
-                (0..cols).map(move |col| TerrainInGrid::new(PositionInGrid { row, col }))
-            })
-            .collect_vec();
-        let mut grid = Grid::from_vec(grid_vec, cols);
+        let mut grid =
+            Grid::filled(rows, cols, ()).map_with_position(|pos, ()| TerrainInGrid::new(pos));
 
         // Insert the trenches into the grid.
+        let origin = SignedPositionInGrid::new(first_row, first_col);
         for trench_pos in trenches {
-            let pos = trench_pos.to_unsigned(first_row, first_col);
-            if let Some(terrain_mut) = grid.get_mut(pos.row, pos.col) {
+            let pos = trench_pos.relative_to(&origin);
+            if let Some(terrain_mut) = pos.and_then(|pos| grid.get_mut(&pos)) {
                 terrain_mut.terrain = Terrain::Trench;
             }
         }
@@ -75,23 +72,10 @@ impl DigPlan {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Display)]
+#[display("{0}")]
 struct TerrainGrid(Grid<TerrainInGrid>);
 
-impl fmt::Display for TerrainGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (position, row) in self.0.iter_rows().with_position() {
-            for terrain in row {
-                write!(f, "{}", terrain)?;
-            }
-            if let Position::First | Position::Middle = position {
-                writeln!(f)?;
-            }
-        }
-        Ok(())
-    }
-}
-
 impl TerrainGrid {
     /// Dig out all terrain that is inside the trenches. Upon completion, all
     /// terrain will be marked as either `Terrain::Trench`,
@@ -107,12 +91,14 @@ impl TerrainGrid {
     /// `Terrain::OutsideTrenches`.
     fn mark_terrain_outside_trench(&mut self) {
         let is_ground_level_terrain = |row, col| {
-            self.0.get(row, col).and_then(|terrain| {
-                terrain
-                    .terrain
-                    .is_ground_level()
-                    .then_some(terrain.position)
-            })
+            self.0
+                .get(&PositionInGrid::new(row, col))
+                .and_then(|terrain| {
+                    terrain
+                        .terrain
+                        .is_ground_level()
+                        .then_some(terrain.position)
+                })
         };
 
         for initial_exterior_terrain_pos in self.search_edges(is_ground_level_terrain) {
@@ -191,7 +177,7 @@ impl TerrainGrid {
     /// `flood fill` algorithm to fill all touching terrain outside the trench
     /// loop with `Terrain::OutsideTrenches`.
     fn flood_fill_outside_trenches(&mut self, terrain_pos: &PositionInGrid) {
-        match self.0.get_mut(terrain_pos.row, terrain_pos.col) {
+        match self.0.get_mut(terrain_pos) {
             Some(terrain_mut) => match terrain_mut.terrain {
                 Terrain::GroundLevel => terrain_mut.terrain = Terrain::OutsideTrenches,
                 Terrain::Trench | Terrain::OutsideTrenches => return,
@@ -206,7 +192,7 @@ impl TerrainGrid {
             if let Some(unvalidated_next_pos) = dir.translate(terrain_pos) {
                 if let Some(next_pos) = self
                     .0
-                    .get(unvalidated_next_pos.row, unvalidated_next_pos.col)
+                    .get(&unvalidated_next_pos)
                     .map(|terrain| terrain.position)
                 {
                     self.flood_fill_outside_trenches(&next_pos);
@@ -236,7 +222,7 @@ enum Terrain {
 #[display("{direction} {steps} ({_color})")]
 struct DigInstruction {
     direction: Direction,
-    steps: isize,
+    steps: usize,
     _color: String,
 }
 
@@ -251,122 +237,38 @@ impl DigInstruction {
     }
 }
 
-type GridIndex = usize;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, new)]
-struct PositionInGrid {
-    row: GridIndex,
-    col: GridIndex,
-}
-
-type SignedGridIndex = isize;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, new)]
-struct SignedPositionInGrid {
-    row: SignedGridIndex,
-    col: SignedGridIndex,
-}
-
-impl SignedPositionInGrid {
-    /// Convert this position to a representation where the row and column
-    /// grid indexes start at 0.
-    fn to_unsigned(self, first_row: SignedGridIndex, first_col: SignedGridIndex) -> PositionInGrid {
-        let row = first_row.abs_diff(self.row);
-        let col = first_col.abs_diff(self.col);
-        PositionInGrid { row, col }
-    }
-}
-
-#[derive(Debug, FromStr, PartialEq, Eq, Copy, Clone, Hash, EnumIter)]
-enum Direction {
-    #[display("U")]
-    North,
-
-    #[display("R")]
-    East,
-
-    #[display("D")]
-    South,
-
-    #[display("L")]
-    West,
-}
-
-impl Direction {
-    /// Translate moving a certain number of `steps` into this direction in a
-    /// grid to changes to the row and column indexes. Return all newly visited
-    /// positions in the grid, where the last iterator element is the furthest
-    /// away from the initial `pos`. Note that the grid is not expected to have
-    /// fixed margins, so returning negative indexes is valid.
-    fn translate_steps(
-        &self,
-        pos: &SignedPositionInGrid,
-        steps: isize,
-    ) -> impl Iterator<Item = SignedPositionInGrid> {
-        // TODO: unnecessary collecting into vec, was done because .rev() returns different type
-        let no_step = |idx| (idx..=idx).collect_vec();
-        let step_forward = |idx| ((idx + 1)..=(idx + steps)).collect_vec();
-        let step_backward = |idx| ((idx - steps)..idx).rev().collect_vec();
-
-        let (row_range, col_range) = match self {
-            Self::North => (step_backward(pos.row), no_step(pos.col)),
-            Self::South => (step_forward(pos.row), no_step(pos.col)),
-            Self::East => (no_step(pos.row), step_forward(pos.col)),
-            Self::West => (no_step(pos.row), step_backward(pos.col)),
-        };
-
-        row_range.into_iter().flat_map(move |row| {
-            col_range
-                .clone()
-                .into_iter()
-                .map(move |col| SignedPositionInGrid { row, col })
-        })
-    }
-
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes. This will never return the same position back. If going
-    /// into some direction would be outside the grid bounds, return `None`.
-    fn translate(&self, pos: &PositionInGrid) -> Option<PositionInGrid> {
-        let no_step = |idx| idx;
-        let step_forward = |idx| idx + 1;
-        let step_backward = |idx: usize| idx.checked_sub(1);
-
-        let (row, col) = match self {
-            Self::North => (step_backward(pos.row)?, no_step(pos.col)),
-            Self::South => (step_forward(pos.row), no_step(pos.col)),
-            Self::East => (no_step(pos.row), step_forward(pos.col)),
-            Self::West => (no_step(pos.row), step_backward(pos.col)?),
-        };
-
-        Some(PositionInGrid { row, col })
-    }
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<DigPlan> {
+    puzzle_input.parse()
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let dig_plan: DigPlan = puzzle_input.parse()?;
-
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(dig_plan: DigPlan) -> anyhow::Result<Solution> {
     let starting_hole = SignedPositionInGrid::new(0, 0);
 
     let terrain_grid = dig_plan.trench_grid(starting_hole);
 
-    println!("{}", terrain_grid);
-
     let inside_trench_count: usize = terrain_grid
         .0
         .iter()
         .filter(|terrain| matches!(terrain.terrain, Terrain::InsideTrenches | Terrain::Trench))
         .count();
 
-    Ok(inside_trench_count.to_string())
+    Ok(inside_trench_count.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide multiple example details as `[(puzzle input, expected solution)]`.
-    pub fn example_details() -> impl Iterator<Item = (&'static str, String)> {
+    pub fn example_details() -> impl Iterator<Item = (&'static str, Solution)> {
         let puzzle_input = indoc! {"
             R 6 (#70c710)
             D 5 (#0dc571)
@@ -410,6 +312,6 @@ pub mod example {
 
         let expected_solution = 62;
 
-        [(puzzle_input, expected_solution.to_string())].into_iter()
+        [(puzzle_input, expected_solution.into())].into_iter()
     }
 }
@@ -1,22 +1,24 @@
+use aoc_grid::{Direction, Grid, GridIndex, PositionInGrid, SignedPositionInGrid};
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_new::new;
-use grid::Grid;
-use itertools::{Itertools, Position};
+use itertools::Itertools;
 use parse_display::{Display, FromStr};
-use std::{fmt, str};
-use strum::{EnumIs, EnumIter, IntoEnumIterator};
+use std::str;
+use strum::{EnumIs, IntoEnumIterator};
 use vec1::vec1;
 
 #[derive(Debug)]
-struct DigPlan(Vec<DigInstruction>);
+pub struct DigPlan(Vec<DigInstruction>);
 
 impl str::FromStr for DigPlan {
     type Err = anyhow::Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let dig_insts = s
             .lines()
-            .map(|line| {
-                line.parse::<DigInstructionParsed>()
-                    .map(DigInstruction::from)
+            .enumerate()
+            .map(|(line_index, line)| {
+                DigInstruction::parse_line(line).map_err(|err| err.in_line(line_index, line))
             })
             .collect::<Result<_, _>>()?;
         Ok(Self(dig_insts))
@@ -24,6 +26,68 @@ impl str::FromStr for DigPlan {
 }
 
 impl DigPlan {
+    /// Starting with the `starting_hole`, execute all instructions in the
+    /// `DigPlan`, and return the number of cubic meters of lava the lagoon
+    /// can hold, i.e. the number of trench and interior terrain tiles.
+    ///
+    /// Instead of digging out a `TerrainGrid`, the trench loop is treated as a
+    /// polygon: The shoelace formula gives its area, and Pick's theorem
+    /// (`area = interior + boundary / 2 - 1`) converts this into the number
+    /// of interior tiles. This only requires the corners of the trench loop,
+    /// so the memory used is proportional to the number of `DigInstruction`s.
+    fn lagoon_size(&self, starting_hole: SignedPositionInGrid) -> usize {
+        let corners = self.dig_trench_corners(starting_hole);
+
+        let double_area: isize = corners
+            .iter()
+            .circular_tuple_windows()
+            .map(|(p1, p2)| p1.row * p2.col - p2.row * p1.col)
+            .sum();
+        let double_area = double_area.unsigned_abs();
+
+        let boundary = self.trench_len();
+
+        // By Pick's theorem: interior = area - boundary / 2 + 1, and the
+        // lagoon consists of both the interior and the boundary.
+        (double_area + boundary) / 2 + 1
+    }
+
+    /// Starting with the `starting_hole`, execute all instructions in the
+    /// `DigPlan`, dig out the whole `TerrainGrid` and count the trench and
+    /// interior terrain tiles. This is only feasible for small inputs, but can
+    /// be used to cross-check `DigPlan::lagoon_size`.
+    fn lagoon_size_from_grid(&self, starting_hole: SignedPositionInGrid) -> usize {
+        let terrain_grid = self.trench_grid(starting_hole);
+
+        terrain_grid
+            .0
+            .iter()
+            .filter(|terrain| matches!(terrain.terrain, Terrain::InsideTrenches | Terrain::Trench))
+            .count()
+    }
+
+    /// The total length of the trench loop.
+    fn trench_len(&self) -> usize {
+        self.0
+            .iter()
+            .map(|dig_inst| dig_inst.steps.unsigned_abs())
+            .sum()
+    }
+
+    /// Starting with the `starting_hole`, execute all instructions in the
+    /// `DigPlan` and return the positions of the trench loop's corners, i.e.
+    /// the positions where each instruction ends.
+    fn dig_trench_corners(&self, starting_hole: SignedPositionInGrid) -> Vec<SignedPositionInGrid> {
+        let mut corners = vec1![starting_hole];
+
+        for dig_inst in self.0.iter() {
+            let next_corner = dig_inst.execute_to_end(corners.last());
+            corners.push(next_corner);
+        }
+
+        corners.into()
+    }
+
     /// Starting with the `starting_hole`, execute all instructions in the
     /// `DigPlan`, and then dig out the interior terrain.
     fn trench_grid(&self, starting_hole: SignedPositionInGrid) -> TerrainGrid {
@@ -62,17 +126,14 @@ impl DigPlan {
         let cols = last_col.abs_diff(first_col) + 1;
 
         // Build up an empty grid filled with `Terrain::GroundLevel`.
-        let grid_vec = (0..rows)
-            .flat_map(|row| {
-                (0..cols).map(move |col| TerrainInGrid::new(PositionInGrid { row, col }))
-            })
-            .collect_vec();
-        let mut grid = Grid::from_vec(grid_vec, cols);
+        let mut grid =
+            Grid::filled(rows, cols, ()).map_with_position(|pos, ()| TerrainInGrid::new(pos));
 
         // Insert the trenches into the grid.
+        let origin = SignedPositionInGrid::new(first_row, first_col);
         for trench_pos in trenches {
-            let pos = trench_pos.to_unsigned(first_row, first_col);
-            if let Some(terrain_mut) = grid.get_mut(pos.row, pos.col) {
+            let pos = trench_pos.relative_to(&origin);
+            if let Some(terrain_mut) = pos.and_then(|pos| grid.get_mut(&pos)) {
                 terrain_mut.terrain = Terrain::Trench;
             }
         }
@@ -81,23 +142,10 @@ impl DigPlan {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Display)]
+#[display("{0}")]
 struct TerrainGrid(Grid<TerrainInGrid>);
 
-impl fmt::Display for TerrainGrid {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        for (position, row) in self.0.iter_rows().with_position() {
-            for terrain in row {
-                write!(f, "{}", terrain)?;
-            }
-            if let Position::First | Position::Middle = position {
-                writeln!(f)?;
-            }
-        }
-        Ok(())
-    }
-}
-
 impl TerrainGrid {
     /// Dig out all terrain that is inside the trenches. Upon completion, all
     /// terrain will be marked as either `Terrain::Trench`,
@@ -113,12 +161,14 @@ impl TerrainGrid {
     /// `Terrain::OutsideTrenches`.
     fn mark_terrain_outside_trench(&mut self) {
         let is_ground_level_terrain = |row, col| {
-            self.0.get(row, col).and_then(|terrain| {
-                terrain
-                    .terrain
-                    .is_ground_level()
-                    .then_some(terrain.position)
-            })
+            self.0
+                .get(&PositionInGrid::new(row, col))
+                .and_then(|terrain| {
+                    terrain
+                        .terrain
+                        .is_ground_level()
+                        .then_some(terrain.position)
+                })
         };
 
         for initial_exterior_terrain_pos in self.search_edges(is_ground_level_terrain) {
@@ -197,7 +247,7 @@ impl TerrainGrid {
     /// `flood fill` algorithm to fill all touching terrain outside the trench
     /// loop with `Terrain::OutsideTrenches`.
     fn flood_fill_outside_trenches(&mut self, terrain_pos: &PositionInGrid) {
-        match self.0.get_mut(terrain_pos.row, terrain_pos.col) {
+        match self.0.get_mut(terrain_pos) {
             Some(terrain_mut) => match terrain_mut.terrain {
                 Terrain::GroundLevel => terrain_mut.terrain = Terrain::OutsideTrenches,
                 Terrain::Trench | Terrain::OutsideTrenches => return,
@@ -212,7 +262,7 @@ impl TerrainGrid {
             if let Some(unvalidated_next_pos) = dir.translate(terrain_pos) {
                 if let Some(next_pos) = self
                     .0
-                    .get(unvalidated_next_pos.row, unvalidated_next_pos.col)
+                    .get(&unvalidated_next_pos)
                     .map(|terrain| terrain.position)
                 {
                     self.flood_fill_outside_trenches(&next_pos);
@@ -245,18 +295,9 @@ struct DigInstruction {
 }
 
 impl str::FromStr for DigInstruction {
-    type Err = anyhow::Error;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // TODO: use nom for better parsing, this is very error prone and would throw terrible error messages
-        let steps = isize::from_str_radix(&s[1..6], 16)?;
-        let direction = match s[6..=6].parse::<usize>()? {
-            0 => Direction::East,
-            1 => Direction::South,
-            2 => Direction::West,
-            3 => Direction::North,
-            dir_index => anyhow::bail!("Unexpected direction index: {}", dir_index),
-        };
-        Ok(Self { direction, steps })
+        Self::parse_color(s, s)
     }
 }
 
@@ -274,16 +315,78 @@ fn test_parsing_color() {
         DigInstruction::new(Direction::East, 356671),
         "#5713f0".parse().unwrap()
     );
+
+    let err = "#70c7".parse::<DigInstruction>().unwrap_err();
+    assert_eq!((1, 2), (err.line, err.column));
+    let err = "#70c7ä".parse::<DigInstruction>().unwrap_err();
+    assert_eq!((1, 2), (err.line, err.column));
+    let err = "70c710".parse::<DigInstruction>().unwrap_err();
+    assert_eq!((1, 1), (err.line, err.column));
+    let err = "#70c714".parse::<DigInstruction>().unwrap_err();
+    assert_eq!((1, 7), (err.line, err.column));
 }
 
 impl DigInstruction {
+    /// Parse from a whole line of the dig plan, e.g. `R 6 (#70c710)`, of which
+    /// only the color holds the actual instruction.
+    fn parse_line(line: &str) -> Result<Self, ParseError> {
+        let parsed: DigInstructionParsed = line.parse()?;
+        // The color is followed only by the closing parenthesis.
+        let color_start = line.len() - ")".len() - parsed.color.len();
+        Self::parse_color(line, &line[color_start..line.len() - ")".len()])
+    }
+
+    /// Parse from a `color` such as `#70c710`, where the first five
+    /// hexadecimal digits are the steps and the last one is the direction.
+    /// The `color` must be a slice of the `line` it appears in, so that errors
+    /// point into the line.
+    fn parse_color(line: &str, color: &str) -> Result<Self, ParseError> {
+        let digits = color
+            .strip_prefix('#')
+            .ok_or_else(|| ParseError::at(line, color, "expected a color starting with `#`"))?;
+        if digits.len() != 6 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ParseError::at(
+                line,
+                digits,
+                "expected six hexadecimal digits",
+            ));
+        }
+
+        // All digits are ASCII, so this splits on a char boundary.
+        let (steps, direction) = digits.split_at(5);
+        let steps = isize::from_str_radix(steps, 16).expect("five hexadecimal digits fit");
+        let direction = match direction {
+            "0" => Direction::East,
+            "1" => Direction::South,
+            "2" => Direction::West,
+            "3" => Direction::North,
+            _ => {
+                return Err(ParseError::at(
+                    line,
+                    direction,
+                    format!("unexpected direction {}, expected 0 to 3", direction),
+                ))
+            }
+        };
+        Ok(Self { direction, steps })
+    }
+
     /// Given a starting position, execute this instruction, and return all
     /// visited positions.
     fn execute(
         &self,
         start_position: &SignedPositionInGrid,
     ) -> impl Iterator<Item = SignedPositionInGrid> {
-        self.direction.translate_steps(start_position, self.steps)
+        self.direction
+            .translate_steps(start_position, self.steps.unsigned_abs())
+    }
+
+    /// Given a starting position, execute this instruction, and return only
+    /// the final position.
+    fn execute_to_end(&self, start_position: &SignedPositionInGrid) -> SignedPositionInGrid {
+        self.direction
+            .translate_signed(start_position, self.steps)
+            .expect("trench corner lies outside of the representable grid")
     }
 }
 
@@ -292,131 +395,75 @@ impl DigInstruction {
 struct DigInstructionParsed {
     _direction: Direction,
     _steps: isize,
-    color: DigInstruction,
+    color: String,
 }
 
-impl From<DigInstructionParsed> for DigInstruction {
-    fn from(dig_inst_parsed: DigInstructionParsed) -> Self {
-        dig_inst_parsed.color
-    }
-}
-
-type GridIndex = usize;
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, new)]
-struct PositionInGrid {
-    row: GridIndex,
-    col: GridIndex,
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<DigPlan> {
+    puzzle_input.parse()
 }
 
-type SignedGridIndex = isize;
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(dig_plan: DigPlan) -> anyhow::Result<Solution> {
+    let starting_hole = SignedPositionInGrid::new(0, 0);
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, new)]
-struct SignedPositionInGrid {
-    row: SignedGridIndex,
-    col: SignedGridIndex,
-}
+    let lagoon_size = dig_plan.lagoon_size(starting_hole);
 
-impl SignedPositionInGrid {
-    /// Convert this position to a representation where the row and column
-    /// grid indexes start at 0.
-    fn to_unsigned(self, first_row: SignedGridIndex, first_col: SignedGridIndex) -> PositionInGrid {
-        let row = first_row.abs_diff(self.row);
-        let col = first_col.abs_diff(self.col);
-        PositionInGrid { row, col }
+    // Small inputs are cheap enough to cross-check against the `TerrainGrid`.
+    if dig_plan.trench_len() <= MAX_CROSS_CHECK_TRENCH_LEN {
+        debug_assert_eq!(lagoon_size, dig_plan.lagoon_size_from_grid(starting_hole));
     }
-}
-
-#[derive(Debug, FromStr, PartialEq, Eq, Copy, Clone, Hash, EnumIter)]
-enum Direction {
-    #[display("U")]
-    North,
 
-    #[display("R")]
-    East,
-
-    #[display("D")]
-    South,
-
-    #[display("L")]
-    West,
+    Ok(lagoon_size.into())
 }
 
-impl Direction {
-    /// Translate moving a certain number of `steps` into this direction in a
-    /// grid to changes to the row and column indexes. Return all newly visited
-    /// positions in the grid, where the last iterator element is the furthest
-    /// away from the initial `pos`. Note that the grid is not expected to have
-    /// fixed margins, so returning negative indexes is valid.
-    fn translate_steps(
-        &self,
-        pos: &SignedPositionInGrid,
-        steps: isize,
-    ) -> impl Iterator<Item = SignedPositionInGrid> {
-        // TODO: unnecessary collecting into vec, was done because .rev() returns different type
-        let no_step = |idx| (idx..=idx).collect_vec();
-        let step_forward = |idx| ((idx + 1)..=(idx + steps)).collect_vec();
-        let step_backward = |idx| ((idx - steps)..idx).rev().collect_vec();
-
-        let (row_range, col_range) = match self {
-            Self::North => (step_backward(pos.row), no_step(pos.col)),
-            Self::South => (step_forward(pos.row), no_step(pos.col)),
-            Self::East => (no_step(pos.row), step_forward(pos.col)),
-            Self::West => (no_step(pos.row), step_backward(pos.col)),
-        };
-
-        row_range.into_iter().flat_map(move |row| {
-            col_range
-                .clone()
-                .into_iter()
-                .map(move |col| SignedPositionInGrid { row, col })
-        })
-    }
-
-    /// Translate moving in this direction in a grid to changes to the row and
-    /// column indexes. This will never return the same position back. If going
-    /// into some direction would be outside the grid bounds, return `None`.
-    fn translate(&self, pos: &PositionInGrid) -> Option<PositionInGrid> {
-        let no_step = |idx| idx;
-        let step_forward = |idx| idx + 1;
-        let step_backward = |idx: usize| idx.checked_sub(1);
-
-        let (row, col) = match self {
-            Self::North => (step_backward(pos.row)?, no_step(pos.col)),
-            Self::South => (step_forward(pos.row), no_step(pos.col)),
-            Self::East => (no_step(pos.row), step_forward(pos.col)),
-            Self::West => (no_step(pos.row), step_backward(pos.col)?),
-        };
-
-        Some(PositionInGrid { row, col })
-    }
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
-    let dig_plan: DigPlan = puzzle_input.parse()?;
+/// The maximum trench length for which `DigPlan::lagoon_size` is
+/// cross-checked against `DigPlan::lagoon_size_from_grid` in debug builds.
+const MAX_CROSS_CHECK_TRENCH_LEN: usize = 1_000;
 
+#[test]
+fn test_lagoon_size() {
+    use Direction::*;
+
+    let dig_plan = DigPlan(
+        [
+            (East, 6),
+            (South, 5),
+            (West, 2),
+            (South, 2),
+            (East, 2),
+            (South, 2),
+            (West, 5),
+            (North, 2),
+            (West, 1),
+            (North, 2),
+            (East, 2),
+            (North, 3),
+            (West, 2),
+            (North, 2),
+        ]
+        .into_iter()
+        .map(|(direction, steps)| DigInstruction::new(direction, steps))
+        .collect(),
+    );
     let starting_hole = SignedPositionInGrid::new(0, 0);
 
-    let terrain_grid = dig_plan.trench_grid(starting_hole);
-
-    println!("{}", terrain_grid);
-
-    let inside_trench_count: usize = terrain_grid
-        .0
-        .iter()
-        .filter(|terrain| matches!(terrain.terrain, Terrain::InsideTrenches | Terrain::Trench))
-        .count();
-
-    Ok(inside_trench_count.to_string())
+    assert_eq!(62, dig_plan.lagoon_size(starting_hole));
+    assert_eq!(62, dig_plan.lagoon_size_from_grid(starting_hole));
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide multiple example details as `[(puzzle input, expected solution)]`.
-    pub fn example_details() -> impl Iterator<Item = (&'static str, String)> {
+    pub fn example_details() -> impl Iterator<Item = (&'static str, Solution)> {
         let puzzle_input = indoc! {"
             R 6 (#70c710)
             D 5 (#0dc571)
@@ -439,6 +486,6 @@ pub mod example {
 
         let expected_solution: usize = 952408144115;
 
-        [(puzzle_input, expected_solution.to_string())].into_iter()
+        [(puzzle_input, expected_solution.into())].into_iter()
     }
 }
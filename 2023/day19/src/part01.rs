@@ -1,25 +1,30 @@
+use anyhow::ensure;
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_new::new;
 use nom::{
     bytes::complete::take_while1,
     character::complete::{alpha1, char, digit1, newline},
     combinator::map_res,
-    error::Error as NomError,
-    multi::separated_list0,
-    Finish, IResult,
+    multi::{separated_list0, separated_list1},
+    IResult,
 };
 use parse_display::FromStr;
-use std::{collections::HashMap, str};
+use std::{
+    collections::{HashMap, HashSet},
+    str,
+};
 use strum::EnumIs;
 
 #[derive(Debug)]
-struct Input {
+pub struct Input {
     workflows: Workflows,
     ratings: Vec<Ratings>,
 }
 
 impl Input {
     /// Parse from:
-    /// ```
+    /// ```text
     /// {workflows}
     ///
     /// {ratings}
@@ -44,17 +49,10 @@ impl Input {
     }
 }
 
-// TODO: this is boilerplate code that should be covered by nom
 impl str::FromStr for Input {
-    type Err = NomError<String>;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match Self::parse(s).finish() {
-            Ok((_remaining, parsed)) => Ok(parsed),
-            Err(NomError { input, code }) => Err(NomError {
-                input: input.to_string(),
-                code,
-            }),
-        }
+        aoc_parse::parse_all(s, Self::parse)
     }
 }
 
@@ -76,6 +74,74 @@ enum Part {
 #[derive(Debug)]
 struct Workflows(HashMap<WorkflowId, Workflow>);
 
+impl Workflows {
+    /// Make sure that there is a workflow to start at, that every workflow
+    /// ends with an unconditional rule, that rules only forward to existing
+    /// workflows, and that no workflow forwards back into itself.
+    fn validate(&self) -> anyhow::Result<()> {
+        let starting_workflow_id = WorkflowId("in".to_string());
+        ensure!(
+            self.0.contains_key(&starting_workflow_id),
+            "there is no workflow `in` to start at"
+        );
+
+        for (id, workflow) in &self.0 {
+            ensure!(
+                matches!(workflow.rules.0.last(), Some(Rule::Unconditional { .. })),
+                "workflow {} does not end with an unconditional rule",
+                id.0
+            );
+
+            for rule in &workflow.rules.0 {
+                if let ActionOnPart::ForwardTo(next_id) = rule.action() {
+                    ensure!(
+                        self.0.contains_key(next_id),
+                        "workflow {} forwards to the unknown workflow {}",
+                        id.0,
+                        next_id.0
+                    );
+                }
+            }
+        }
+
+        self.ensure_acyclic(
+            &starting_workflow_id,
+            &mut HashSet::new(),
+            &mut HashSet::new(),
+        )
+    }
+
+    /// Make sure that no workflow reachable from the one with id
+    /// `workflow_id` forwards back into itself, neither directly nor via other
+    /// workflows, using a depth-first search. `visiting` holds the workflows
+    /// on the current path, and `visited` those already known to be acyclic.
+    fn ensure_acyclic<'a>(
+        &'a self,
+        workflow_id: &'a WorkflowId,
+        visiting: &mut HashSet<&'a WorkflowId>,
+        visited: &mut HashSet<&'a WorkflowId>,
+    ) -> anyhow::Result<()> {
+        if visited.contains(workflow_id) {
+            return Ok(());
+        }
+        ensure!(
+            visiting.insert(workflow_id),
+            "workflow {} forwards back into itself",
+            workflow_id.0
+        );
+
+        for rule in &self.0[workflow_id].rules.0 {
+            if let ActionOnPart::ForwardTo(next_id) = rule.action() {
+                self.ensure_acyclic(next_id, visiting, visited)?;
+            }
+        }
+
+        visiting.remove(workflow_id);
+        visited.insert(workflow_id);
+        Ok(())
+    }
+}
+
 impl Ratings {
     fn evaluate(&self, workflows: &Workflows) -> FinalState {
         let starting_workflow_id = WorkflowId("in".to_string());
@@ -86,7 +152,7 @@ impl Ratings {
             let workflow = workflows
                 .0
                 .get(cur_workflow_id)
-                .expect("unknown workflow name/id");
+                .expect("the workflows were validated to only forward to existing ones");
 
             match workflow.rules.evaluate(self) {
                 ActionOnPart::Accept => return FinalState::Accepted,
@@ -132,12 +198,11 @@ impl Rules {
     /// Try evaluating each of the ratings according to these rules. Return
     /// the action that belongs to the first successful rule evaluation.
     fn evaluate(&self, ratings: &Ratings) -> &ActionOnPart {
-        self
-            .0
+        self.0
             .iter()
             .flat_map(|rule| ratings.0.iter().map(move |rating| (rule, rating)))
             .find_map(|(rule, rating)| rule.evaluate(rating))
-            .expect("problem statement doesn't clarify what should happen if no rule is fulfilled for a certain rating")
+            .expect("every workflow was validated to end with an unconditional rule")
     }
 }
 
@@ -198,7 +263,16 @@ enum Rule {
 }
 
 impl Rule {
-    /// Parse from `{part}={rating}`.
+    /// The action to perform if this rule applies.
+    fn action(&self) -> &ActionOnPart {
+        match self {
+            Rule::Greater { workflow, .. }
+            | Rule::Less { workflow, .. }
+            | Rule::Unconditional { workflow } => workflow,
+        }
+    }
+
+    /// Parse from `{part}{<|>}{value}:{target}` or `{target}`.
     fn parse(input: &str) -> IResult<&str, Self> {
         let mut rule_parser = map_res(
             // TODO: very hacky, here we capture all chars that are valid in our parse::<Rule>. Instead, make a native nom parser for Rule.
@@ -257,7 +331,7 @@ struct Ratings(Vec<Rating>);
 impl Ratings {
     /// Parse from `{{rating},{rating},...}`.
     fn parse(input: &str) -> IResult<&str, Self> {
-        let mut ratings_parser = separated_list0(char(','), Rating::parse);
+        let mut ratings_parser = separated_list1(char(','), Rating::parse);
 
         let (input, _) = char('{')(input)?;
         let (input, rules) = ratings_parser(input)?;
@@ -303,10 +377,15 @@ fn test_parse_rating() {
     assert_eq!(Rating::new(Part::Aerodynamic, 10), "a=10".parse().unwrap());
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Input> {
     let input: Input = puzzle_input.parse()?;
+    input.workflows.validate()?;
+    Ok(input)
+}
 
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(input: Input) -> anyhow::Result<Solution> {
     let accepted_parts_ratings_sum: usize = input
         .ratings
         .iter()
@@ -314,15 +393,21 @@ pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
         .flat_map(|ratings| ratings.0.iter().map(|rating| rating.rating))
         .sum();
 
-    Ok(accepted_parts_ratings_sum.to_string())
+    Ok(accepted_parts_ratings_sum.into())
+}
+
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide multiple example details as `[(puzzle input, expected solution)]`.
-    pub fn example_details() -> impl Iterator<Item = (&'static str, String)> {
+    pub fn example_details() -> impl Iterator<Item = (&'static str, Solution)> {
         let puzzle_input = indoc! {"
             px{a<2006:qkq,m>2090:A,rfg}
             pv{a>1716:R,A}
@@ -344,6 +429,6 @@ pub mod example {
         "};
         let expected_solution = 19114;
 
-        [(puzzle_input, expected_solution.to_string())].into_iter()
+        [(puzzle_input, expected_solution.into())].into_iter()
     }
 }
@@ -1,27 +1,29 @@
+use anyhow::ensure;
+use aoc_parse::ParseError;
+use aoc_solution::Solution;
 use derive_new::new;
-use indicatif::ParallelProgressIterator;
 use nom::{
     bytes::complete::take_while1,
     character::complete::{alpha1, char, digit1, newline},
     combinator::map_res,
-    error::Error as NomError,
-    multi::separated_list0,
-    Finish, IResult,
+    multi::{separated_list0, separated_list1},
+    IResult,
 };
 use parse_display::FromStr;
-use rayon::iter::{IntoParallelIterator, ParallelIterator};
-use std::{collections::HashMap, str};
-use strum::EnumIs;
+use std::{
+    collections::{HashMap, HashSet},
+    ops::Range,
+    str,
+};
 
 #[derive(Debug)]
-struct Input {
+pub struct Input {
     workflows: Workflows,
-    ratings: Vec<Ratings>,
 }
 
 impl Input {
     /// Parse from:
-    /// ```
+    /// ```text
     /// {workflows}
     ///
     /// {ratings}
@@ -33,7 +35,9 @@ impl Input {
         let (input, parsed_workflows) = workflows_parser(input)?;
         let (input, _) = newline(input)?;
         let (input, _) = newline(input)?;
-        let (input, ratings) = ratings_parser(input)?;
+        // The individual ratings are irrelevant, since all possible
+        // combinations of ratings are considered.
+        let (input, _ratings) = ratings_parser(input)?;
 
         let workflows = Workflows(
             parsed_workflows
@@ -42,25 +46,18 @@ impl Input {
                 .collect(),
         );
 
-        Ok((input, Self { workflows, ratings }))
+        Ok((input, Self { workflows }))
     }
 }
 
-// TODO: this is boilerplate code that should be covered by nom
 impl str::FromStr for Input {
-    type Err = NomError<String>;
+    type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match Self::parse(s).finish() {
-            Ok((_remaining, parsed)) => Ok(parsed),
-            Err(NomError { input, code }) => Err(NomError {
-                input: input.to_string(),
-                code,
-            }),
-        }
+        aoc_parse::parse_all(s, Self::parse)
     }
 }
 
-#[derive(Debug, FromStr, PartialEq, Eq)]
+#[derive(Debug, FromStr, PartialEq, Eq, Clone, Copy)]
 enum Part {
     #[display("x")]
     ExtremelyCoolLooking,
@@ -78,24 +75,93 @@ enum Part {
 #[derive(Debug)]
 struct Workflows(HashMap<WorkflowId, Workflow>);
 
-impl Ratings {
-    fn evaluate(&self, workflows: &Workflows) -> FinalState {
+impl Workflows {
+    /// Make sure that there is a workflow to start at, that every workflow
+    /// ends with an unconditional rule, that rules only forward to existing
+    /// workflows, and that no workflow forwards back into itself.
+    fn validate(&self) -> anyhow::Result<()> {
         let starting_workflow_id = WorkflowId("in".to_string());
+        ensure!(
+            self.0.contains_key(&starting_workflow_id),
+            "there is no workflow `in` to start at"
+        );
 
-        let mut cur_workflow_id = &starting_workflow_id;
+        for (id, workflow) in &self.0 {
+            ensure!(
+                matches!(workflow.rules.0.last(), Some(Rule::Unconditional { .. })),
+                "workflow {} does not end with an unconditional rule",
+                id.0
+            );
+
+            for rule in &workflow.rules.0 {
+                if let ActionOnPart::ForwardTo(next_id) = rule.action() {
+                    ensure!(
+                        self.0.contains_key(next_id),
+                        "workflow {} forwards to the unknown workflow {}",
+                        id.0,
+                        next_id.0
+                    );
+                }
+            }
+        }
 
-        loop {
-            let workflow = workflows
-                .0
-                .get(cur_workflow_id)
-                .expect("unknown workflow name/id");
+        self.ensure_acyclic(
+            &starting_workflow_id,
+            &mut HashSet::new(),
+            &mut HashSet::new(),
+        )
+    }
 
-            match workflow.rules.evaluate(self) {
-                ActionOnPart::Accept => return FinalState::Accepted,
-                ActionOnPart::Reject => return FinalState::Rejected,
-                ActionOnPart::ForwardTo(next_workflow_id) => cur_workflow_id = next_workflow_id,
-            };
+    /// Make sure that no workflow reachable from the one with id
+    /// `workflow_id` forwards back into itself, neither directly nor via other
+    /// workflows, using a depth-first search. `visiting` holds the workflows
+    /// on the current path, and `visited` those already known to be acyclic.
+    fn ensure_acyclic<'a>(
+        &'a self,
+        workflow_id: &'a WorkflowId,
+        visiting: &mut HashSet<&'a WorkflowId>,
+        visited: &mut HashSet<&'a WorkflowId>,
+    ) -> anyhow::Result<()> {
+        if visited.contains(workflow_id) {
+            return Ok(());
+        }
+        ensure!(
+            visiting.insert(workflow_id),
+            "workflow {} forwards back into itself",
+            workflow_id.0
+        );
+
+        for rule in &self.0[workflow_id].rules.0 {
+            if let ActionOnPart::ForwardTo(next_id) = rule.action() {
+                self.ensure_acyclic(next_id, visiting, visited)?;
+            }
         }
+
+        visiting.remove(workflow_id);
+        visited.insert(workflow_id);
+        Ok(())
+    }
+
+    /// Count the number of rating combinations inside `rating_ranges` that
+    /// are accepted when starting at the workflow with id `workflow_id`.
+    fn count_accepted(&self, workflow_id: &WorkflowId, rating_ranges: RatingRanges) -> usize {
+        let workflow = self
+            .0
+            .get(workflow_id)
+            .expect("the workflows were validated to only forward to existing ones");
+
+        workflow
+            .rules
+            .split(rating_ranges)
+            .into_iter()
+            .map(|(rating_ranges, action)| match action {
+                ActionOnPart::Accept => rating_ranges.size(),
+                ActionOnPart::Reject => 0,
+                ActionOnPart::ForwardTo(next_workflow_id) => {
+                    self.count_accepted(next_workflow_id, rating_ranges)
+                }
+            })
+            .sum()
     }
 }
 
@@ -131,15 +197,28 @@ impl ParsedWorkflow {
 struct Rules(Vec<Rule>);
 
 impl Rules {
-    /// Try evaluating each of the ratings according to these rules. Return
-    /// the action that belongs to the first successful rule evaluation.
-    fn evaluate(&self, ratings: &Ratings) -> &ActionOnPart {
-        self
-            .0
-            .iter()
-            .flat_map(|rule| ratings.0.iter().map(move |rating| (rule, rating)))
-            .find_map(|(rule, rating)| rule.evaluate(rating))
-            .expect("problem statement doesn't clarify what should happen if no rule is fulfilled for a certain rating")
+    /// Split the rating ranges according to these rules. Each resulting part
+    /// of the rating ranges is paired with the action of the first rule that
+    /// it fulfills.
+    fn split(&self, rating_ranges: RatingRanges) -> Vec<(RatingRanges, &ActionOnPart)> {
+        let mut splits = Vec::new();
+
+        let mut remaining = Some(rating_ranges);
+        for rule in self.0.iter() {
+            let Some(rating_ranges) = remaining else {
+                break;
+            };
+            let (fulfilled, unfulfilled) = rule.split(rating_ranges);
+            splits.extend(fulfilled);
+            remaining = unfulfilled;
+        }
+
+        assert!(
+            remaining.is_none(),
+            "problem statement doesn't clarify what should happen if no rule is fulfilled for certain ratings"
+        );
+
+        splits
     }
 }
 
@@ -200,7 +279,16 @@ enum Rule {
 }
 
 impl Rule {
-    /// Parse from `{part}={rating}`.
+    /// The action to perform if this rule applies.
+    fn action(&self) -> &ActionOnPart {
+        match self {
+            Rule::Greater { workflow, .. }
+            | Rule::Less { workflow, .. }
+            | Rule::Unconditional { workflow } => workflow,
+        }
+    }
+
+    /// Parse from `{part}{<|>}{value}:{target}` or `{target}`.
     fn parse(input: &str) -> IResult<&str, Self> {
         let mut rule_parser = map_res(
             // TODO: very hacky, here we capture all chars that are valid in our parse::<Rule>. Instead, make a native nom parser for Rule.
@@ -213,26 +301,52 @@ impl Rule {
         Ok((input, rule))
     }
 
-    /// If this rule can be applied to this rating, evaluate the rule and
-    /// return the next action.
-    fn evaluate(&self, rating: &Rating) -> Option<&ActionOnPart> {
-        match self {
-            Rule::Unconditional { workflow } => Some(workflow),
+    /// Split the rating ranges into the part that fulfills this rule's
+    /// condition, paired with the next action, and the part that doesn't.
+    /// Either part may be empty.
+    fn split(
+        &self,
+        rating_ranges: RatingRanges,
+    ) -> (Option<(RatingRanges, &ActionOnPart)>, Option<RatingRanges>) {
+        let (fulfilled, unfulfilled, workflow) = match self {
+            Rule::Unconditional { workflow } => (Some(rating_ranges), None, workflow),
             Rule::Greater {
                 part,
                 num,
                 workflow,
-            } if part == &rating.part && &rating.rating > num => Some(workflow),
+            } => {
+                let (unfulfilled, fulfilled) = rating_ranges.split_at(*part, num + 1);
+                (fulfilled, unfulfilled, workflow)
+            }
             Rule::Less {
                 part,
                 num,
                 workflow,
-            } if part == &rating.part && &rating.rating < num => Some(workflow),
-            _ => None,
-        }
+            } => {
+                let (fulfilled, unfulfilled) = rating_ranges.split_at(*part, *num);
+                (fulfilled, unfulfilled, workflow)
+            }
+        };
+
+        (fulfilled.map(|ranges| (ranges, workflow)), unfulfilled)
     }
 }
 
+#[test]
+fn test_rule_split() {
+    let rule = Rule::Less {
+        part: Part::ExtremelyCoolLooking,
+        num: 42,
+        workflow: ActionOnPart::Accept,
+    };
+    let (fulfilled, unfulfilled) = rule.split(RatingRanges::new(1..4001));
+
+    let (fulfilled, action) = fulfilled.unwrap();
+    assert_eq!(ActionOnPart::Accept, *action);
+    assert_eq!(41 * 4000 * 4000 * 4000, fulfilled.size());
+    assert_eq!(3959 * 4000 * 4000 * 4000, unfulfilled.unwrap().size());
+}
+
 /// The action to perform on a part.
 #[derive(Debug, FromStr, PartialEq, Eq)]
 enum ActionOnPart {
@@ -246,11 +360,39 @@ enum ActionOnPart {
     ForwardTo(WorkflowId),
 }
 
-/// The final state of a part.
-#[derive(Debug, EnumIs)]
-enum FinalState {
-    Accepted,
-    Rejected,
+/// A 4-dimensional box of ratings, containing one range of possible ratings
+/// for each `Part`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RatingRanges([Range<usize>; 4]);
+
+impl RatingRanges {
+    /// Create rating ranges where every `Part` has the same range.
+    fn new(range: Range<usize>) -> Self {
+        Self([range.clone(), range.clone(), range.clone(), range])
+    }
+
+    /// The number of rating combinations contained in these rating ranges.
+    fn size(&self) -> usize {
+        self.0.iter().map(|range| range.len()).product()
+    }
+
+    /// Split into the rating ranges where the rating of `part` is below
+    /// `split`, and those where it is at or above `split`. Empty rating ranges
+    /// are returned as `None`.
+    fn split_at(&self, part: Part, split: usize) -> (Option<Self>, Option<Self>) {
+        let range = &self.0[part as usize];
+        let split = split.clamp(range.start, range.end);
+
+        let with_range = |range: Range<usize>| {
+            (!range.is_empty()).then(|| {
+                let mut rating_ranges = self.clone();
+                rating_ranges.0[part as usize] = range;
+                rating_ranges
+            })
+        };
+
+        (with_range(range.start..split), with_range(split..range.end))
+    }
 }
 
 // TODO: optimization: make Ratings a [Rating; 4]
@@ -261,7 +403,7 @@ struct Ratings(Vec<Rating>);
 impl Ratings {
     /// Parse from `{{rating},{rating},...}`.
     fn parse(input: &str) -> IResult<&str, Self> {
-        let mut ratings_parser = separated_list0(char(','), Rating::parse);
+        let mut ratings_parser = separated_list1(char(','), Rating::parse);
 
         let (input, _) = char('{')(input)?;
         let (input, rules) = ratings_parser(input)?;
@@ -307,41 +449,49 @@ fn test_parse_rating() {
     assert_eq!(Rating::new(Part::Aerodynamic, 10), "a=10".parse().unwrap());
 }
 
-/// Solve the problem and return the solution as a `String`.
-pub fn solve(puzzle_input: &str) -> anyhow::Result<String> {
+#[test]
+fn test_cyclic_workflows() {
+    let err = parse("in{x<5:in,A}\n\n{x=1,m=2,a=3,s=4}").unwrap_err();
+    assert_eq!("workflow in forwards back into itself", err.to_string());
+
+    let err = parse("in{x<5:ab,A}\nab{m>2:A,cd}\ncd{in}\n\n{x=1,m=2,a=3,s=4}").unwrap_err();
+    assert!(err.to_string().ends_with("forwards back into itself"));
+
+    // Forwarding to the same workflow from different ones is no cycle.
+    assert!(parse("in{x<5:ab,cd}\nab{cd}\ncd{A}\n\n{x=1,m=2,a=3,s=4}").is_ok());
+}
+
+/// Parse the puzzle input.
+pub fn parse(puzzle_input: &str) -> anyhow::Result<Input> {
     let input: Input = puzzle_input.parse()?;
+    input.workflows.validate()?;
+    Ok(input)
+}
+
+/// Solve the problem for the parsed puzzle input and return the solution.
+pub fn solve_parsed(input: Input) -> anyhow::Result<Solution> {
+    let starting_workflow_id = WorkflowId("in".to_string());
+    let all_combinations = RatingRanges::new(1..4001);
+
+    let accepted_combinations = input
+        .workflows
+        .count_accepted(&starting_workflow_id, all_combinations);
+
+    Ok(accepted_combinations.into())
+}
 
-    let range = || 1..=4000;
-
-    let all_combinations: Vec<(usize, usize, usize, usize)> = range()
-        .flat_map(move |x| {
-            range().flat_map(move |m| range().flat_map(move |a| range().map(move |s| (x, m, a, s))))
-        })
-        .collect();
-
-    let accepted_combinations = all_combinations
-        .into_par_iter()
-        .progress()
-        .map(|(x, m, a, s)| {
-            Ratings(vec![
-                Rating::new(Part::ExtremelyCoolLooking, x),
-                Rating::new(Part::Musical, m),
-                Rating::new(Part::Aerodynamic, a),
-                Rating::new(Part::Shiny, s),
-            ])
-        })
-        .filter(|ratings| ratings.evaluate(&input.workflows).is_accepted())
-        .count();
-
-    Ok(accepted_combinations.to_string())
+/// Solve the problem and return the solution.
+pub fn solve(puzzle_input: &str) -> anyhow::Result<Solution> {
+    solve_parsed(parse(puzzle_input)?)
 }
 
 #[cfg(test)]
 pub mod example {
+    use aoc_solution::Solution;
     use indoc::indoc;
 
     /// Provide multiple example details as `[(puzzle input, expected solution)]`.
-    pub fn example_details() -> impl Iterator<Item = (&'static str, String)> {
+    pub fn example_details() -> impl Iterator<Item = (&'static str, Solution)> {
         let puzzle_input = indoc! {"
             px{a<2006:qkq,m>2090:A,rfg}
             pv{a>1716:R,A}
@@ -363,6 +513,6 @@ pub mod example {
         "};
         let expected_solution: usize = 167409079868000;
 
-        [(puzzle_input, expected_solution.to_string())].into_iter()
+        [(puzzle_input, expected_solution.into())].into_iter()
     }
 }